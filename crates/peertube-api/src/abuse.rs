@@ -0,0 +1,75 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::{json, Value};
+
+/// Reasons that can be given with a report, with the name used by the API and a description
+pub const PREDEFINED_REASONS: [(&str, &str); 8] = [
+    ("violentOrRepulsive", "Violent or repulsive"),
+    ("hatefulOrAbusive", "Hateful or abusive"),
+    ("spamOrMisleading", "Spam or misleading"),
+    ("privacy", "Privacy"),
+    ("rights", "Copyright"),
+    ("serverRules", "Breaks the server rules"),
+    ("thumbnails", "Thumbnails"),
+    ("captions", "Captions"),
+];
+
+/// Content reported to the moderators of the instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reported {
+    Video {
+        uuid: String,
+        /// Part of the video concerned, in seconds
+        range: Option<(u64, u64)>,
+    },
+    Comment(u64),
+}
+
+impl Reported {
+    /// Body of the request to `/api/v1/abuses`
+    pub(crate) fn to_json(&self, reason: &str, predefined: &[&str]) -> Value {
+        let mut body = json!({
+            "reason": reason,
+            "predefinedReasons": predefined,
+        });
+        match self {
+            Reported::Video { uuid, range } => {
+                body["video"] = json!({ "id": uuid });
+                if let Some((start, end)) = range {
+                    body["video"]["startAt"] = json!(start);
+                    body["video"]["endAt"] = json!(end);
+                }
+            }
+            Reported::Comment(id) => body["comment"] = json!({ "id": id }),
+        }
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn body() {
+        let video = Reported::Video {
+            uuid: "9c9de5e8".into(),
+            range: Some((80, 125)),
+        };
+        assert_eq!(
+            video.to_json("Spam", &["spamOrMisleading"]).to_string(),
+            r#"{"predefinedReasons":["spamOrMisleading"],"reason":"Spam","video":{"endAt":125,"id":"9c9de5e8","startAt":80}}"#
+        );
+        assert_eq!(
+            Reported::Comment(42).to_json("Rude", &[]).to_string(),
+            r#"{"comment":{"id":42},"predefinedReasons":[],"reason":"Rude"}"#
+        );
+    }
+}
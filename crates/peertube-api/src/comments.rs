@@ -11,9 +11,11 @@ use std::convert::TryFrom;
 use time::OffsetDateTime;
 
 pub struct Comment {
+    id: u64,
     content: String,
     url: String,
     created_at: OffsetDateTime,
+    replies: u64,
     author: Channel,
 }
 
@@ -22,9 +24,11 @@ impl TryFrom<peertube_ser::comments::Comment> for Comment {
     fn try_from(comment: peertube_ser::comments::Comment) -> Result<Self, ()> {
         match (comment.isDeleted, comment.url, comment.account) {
             (false, Some(url), Some(account)) => Ok(Comment {
+                id: comment.id,
                 content: comment.text,
                 url,
                 created_at: comment.createdAt,
+                replies: comment.totalReplies,
                 author: account.into(),
             }),
             _ => Err(()),
@@ -33,6 +37,11 @@ impl TryFrom<peertube_ser::comments::Comment> for Comment {
 }
 
 impl Comment {
+    /// Id of the comment on the instance it was fetched from
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     pub fn content(&self) -> &str {
         &self.content
     }
@@ -49,7 +58,15 @@ impl Comment {
         &self.author.host
     }
 
+    pub fn author_handle(&self) -> String {
+        format!("{}@{}", self.author.name, self.author.host)
+    }
+
     pub fn created_at(&self) -> OffsetDateTime {
         self.created_at
     }
+
+    pub fn replies(&self) -> u64 {
+        self.replies
+    }
 }
@@ -17,6 +17,8 @@ pub enum Error {
 
     /// contains the length of the array
     OutOfBound(usize),
+    /// The request requires to be logged in
+    NotLoggedIn,
     Io(Arc<io::Error>),
     Serde(Arc<serde_json::Error>),
 }
@@ -28,6 +30,10 @@ impl fmt::Display for Error {
             Error::Io(err) => write!(f, "Connection error: {err}"),
             Error::NoContent => write!(f, "No content"),
             Error::OutOfBound(len) => write!(f, "Out of bound access, the array is of len {len}"),
+            Error::NotLoggedIn => write!(
+                f,
+                "This requires to be logged in, see [account] in the config"
+            ),
             Error::Serde(err) => write!(f, "Deserialisation error: {err}"),
         }
     }
@@ -39,7 +45,7 @@ impl error::Error for Error {
             Error::Ureq(err) => Some(&**err),
             Error::Io(err) => Some(&**err),
             Error::Serde(err) => Some(&**err),
-            Error::NoContent | Error::OutOfBound(_) => None,
+            Error::NoContent | Error::OutOfBound(_) | Error::NotLoggedIn => None,
         }
     }
 }
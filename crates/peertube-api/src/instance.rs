@@ -8,24 +8,35 @@
 
 use std::borrow::Cow;
 use std::convert::TryFrom;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
+use peertube_ser::account::{BlockedAccounts, BlockedServers, OAuthClient, Token};
 use peertube_ser::channels::Channels;
 use peertube_ser::video::{Description, File, StreamingPlaylist, Video as FullVideo};
-use peertube_ser::{Comments, Videos};
+use peertube_ser::{Captions, Comments, Videos};
 use peertube_viewer_utils::to_https;
 
+use crate::abuse::Reported;
 use crate::channels::Channel;
 use crate::comments::Comment;
 use crate::error;
 use crate::video::Video;
 
+/// Tokens of the logged in user, with what is needed to refresh them
+struct Session {
+    client: OAuthClient,
+    access_token: String,
+    refresh_token: String,
+}
+
 /// Connection to an instance
 /// Video that this instance returns through `search_videos` will all use the instance which
 /// created them. This avoids connecting to many distinct instances.
 pub struct Instance {
     host: String,
-    user_agent: Option<String>,
+    user_agent: RwLock<Option<String>>,
+    /// Set once logged in
+    session: RwLock<Option<Session>>,
     include_nsfw: &'static str,
     local: bool,
 
@@ -46,7 +57,8 @@ impl Instance {
     ) -> Arc<Instance> {
         Arc::new(Instance {
             host,
-            user_agent,
+            user_agent: RwLock::new(user_agent),
+            session: RwLock::new(None),
             include_nsfw: nsfw_string(include_nsfw),
             local,
             is_search,
@@ -55,13 +67,186 @@ impl Instance {
 
     /// Adds the user agent if there is one
     fn add_user_agent(&self, req: ureq::Request) -> ureq::Request {
-        if let Some(user_agent) = &self.user_agent {
+        if let Some(user_agent) = &*self.user_agent.read().unwrap() {
             req.set("User-Agent", user_agent)
         } else {
             req
         }
     }
 
+    /// Change the user agent used by the following requests
+    pub fn set_user_agent(&self, user_agent: Option<String>) {
+        *self.user_agent.write().unwrap() = user_agent;
+    }
+
+    /// Send a request to the instance itself with the access token
+    ///
+    /// The access token expires after a day, it is refreshed once if the request is refused
+    fn authenticated(
+        &self,
+        req: ureq::Request,
+        send: impl Fn(ureq::Request) -> error::Result<ureq::Response>,
+    ) -> error::Result<ureq::Response> {
+        let authorize = |req: ureq::Request| match &*self.session.read().unwrap() {
+            Some(session) => Ok(self
+                .add_user_agent(req)
+                .set("Authorization", &format!("Bearer {}", session.access_token))),
+            None => Err(error::Error::NotLoggedIn),
+        };
+        match send(authorize(req.clone())?) {
+            Err(error::Error::Ureq(err)) if matches!(*err, ureq::Error::Status(401, _)) => {
+                self.refresh()?;
+                send(authorize(req)?)
+            }
+            res => res,
+        }
+    }
+
+    fn request_token(&self, form: &[(&str, &str)]) -> error::Result<Token> {
+        let url = format!("{}/api/v1/users/token", self.host);
+        Ok(serde_json::from_str(
+            &self
+                .add_user_agent(ureq::post(&url))
+                .send_form(form)?
+                .into_string()?,
+        )?)
+    }
+
+    /// Log in with the credentials of an account of the instance
+    pub fn login(&self, username: &str, password: &str) -> error::Result<()> {
+        let url = format!("{}/api/v1/oauth-clients/local", self.host);
+        let client: OAuthClient =
+            serde_json::from_str(&self.add_user_agent(ureq::get(&url)).call()?.into_string()?)?;
+
+        let token = self.request_token(&[
+            ("client_id", &client.client_id),
+            ("client_secret", &client.client_secret),
+            ("grant_type", "password"),
+            ("username", username),
+            ("password", password),
+        ])?;
+        *self.session.write().unwrap() = Some(Session {
+            client,
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+        });
+        Ok(())
+    }
+
+    /// Get a new access token with the refresh token
+    fn refresh(&self) -> error::Result<()> {
+        let (client_id, client_secret, refresh_token) = match &*self.session.read().unwrap() {
+            Some(s) => (
+                s.client.client_id.clone(),
+                s.client.client_secret.clone(),
+                s.refresh_token.clone(),
+            ),
+            None => return Err(error::Error::NotLoggedIn),
+        };
+        let token = self.request_token(&[
+            ("client_id", &client_id),
+            ("client_secret", &client_secret),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+        ])?;
+        if let Some(session) = &mut *self.session.write().unwrap() {
+            session.access_token = token.access_token;
+            session.refresh_token = token.refresh_token;
+        }
+        Ok(())
+    }
+
+    pub fn is_logged_in(&self) -> bool {
+        self.session.read().unwrap().is_some()
+    }
+
+    /// Handles of the accounts muted by the logged in user
+    pub fn muted_accounts(&self) -> error::Result<Vec<String>> {
+        let url = format!("{}/api/v1/users/me/blocklist/accounts", self.host);
+        let mut res = Vec::new();
+        loop {
+            let req = ureq::get(&url)
+                .query("count", "100")
+                .query("start", &res.len().to_string());
+            let resp = self.authenticated(req, |req| Ok(req.call()?))?;
+            let page: BlockedAccounts = serde_json::from_str(&resp.into_string()?)?;
+            let len = page.data.len();
+            res.extend(
+                page.data
+                    .into_iter()
+                    .map(|b| format!("{}@{}", b.blockedAccount.name, b.blockedAccount.host)),
+            );
+            if len == 0 || res.len() >= page.total {
+                return Ok(res);
+            }
+        }
+    }
+
+    /// Hosts of the instances muted by the logged in user
+    pub fn muted_instances(&self) -> error::Result<Vec<String>> {
+        let url = format!("{}/api/v1/users/me/blocklist/servers", self.host);
+        let mut res = Vec::new();
+        loop {
+            let req = ureq::get(&url)
+                .query("count", "100")
+                .query("start", &res.len().to_string());
+            let resp = self.authenticated(req, |req| Ok(req.call()?))?;
+            let page: BlockedServers = serde_json::from_str(&resp.into_string()?)?;
+            let len = page.data.len();
+            res.extend(page.data.into_iter().map(|b| b.blockedServer.host));
+            if len == 0 || res.len() >= page.total {
+                return Ok(res);
+            }
+        }
+    }
+
+    /// Mute an account (`name@host`) for the logged in user
+    pub fn mute_account(&self, handle: &str) -> error::Result<()> {
+        let url = format!("{}/api/v1/users/me/blocklist/accounts", self.host);
+        self.authenticated(ureq::post(&url), |req| {
+            Ok(req.send_form(&[("accountName", handle)])?)
+        })?;
+        Ok(())
+    }
+
+    pub fn unmute_account(&self, handle: &str) -> error::Result<()> {
+        let url = format!("{}/api/v1/users/me/blocklist/accounts/{handle}", self.host);
+        self.authenticated(ureq::delete(&url), |req| Ok(req.call()?))?;
+        Ok(())
+    }
+
+    /// Mute all the accounts of an instance for the logged in user
+    pub fn mute_instance(&self, host: &str) -> error::Result<()> {
+        let url = format!("{}/api/v1/users/me/blocklist/servers", self.host);
+        self.authenticated(
+            ureq::post(&url),
+            |req| Ok(req.send_form(&[("host", host)])?),
+        )?;
+        Ok(())
+    }
+
+    pub fn unmute_instance(&self, host: &str) -> error::Result<()> {
+        let url = format!("{}/api/v1/users/me/blocklist/servers/{host}", self.host);
+        self.authenticated(ureq::delete(&url), |req| Ok(req.call()?))?;
+        Ok(())
+    }
+
+    /// Report a video or a comment to the moderators of the instance
+    pub fn report(
+        &self,
+        reported: &Reported,
+        reason: &str,
+        predefined: &[&str],
+    ) -> error::Result<()> {
+        let url = format!("{}/api/v1/abuses", self.host);
+        let body = reported.to_json(reason, predefined).to_string();
+        self.authenticated(
+            ureq::post(&url).set("Content-Type", "application/json"),
+            |req| Ok(req.send_string(&body)?),
+        )?;
+        Ok(())
+    }
+
     /// Perform a search for the given query
     pub fn search_videos(
         self: &Arc<Instance>,
@@ -251,6 +436,39 @@ impl Instance {
         Ok((video.files, video.streamingPlaylists))
     }
 
+    /// Fetch the captions of a video
+    /// Returns the language code and the url of each caption
+    pub fn video_captions(
+        self: &Arc<Instance>,
+        host: &str,
+        uuid: &str,
+    ) -> error::Result<Vec<(String, String)>> {
+        let url = format!("{}/api/v1/videos/{}/captions", self.api_host(host), uuid);
+
+        let req = self.add_user_agent(ureq::get(&url));
+        let captions: Captions = serde_json::from_str(&req.call()?.into_string()?)?;
+        Ok(captions
+            .data
+            .into_iter()
+            .map(|c| {
+                (
+                    c.language.id.unwrap_or(c.language.label),
+                    self.resource_url(host, &c.captionPath),
+                )
+            })
+            .collect())
+    }
+
+    /// Url of a static resource (thumbnail, caption...) from its path
+    /// The path is relative to the instance the data was fetched from
+    pub fn resource_url(&self, host: &str, path: &str) -> String {
+        if path.starts_with("https://") || path.starts_with("http://") {
+            path.to_owned()
+        } else {
+            format!("{}{}", self.api_host(host), path)
+        }
+    }
+
     pub fn channel_url(&self, host: &str, channel: &Channel) -> String {
         format!(
             "{}/video-channels/{}/videos",
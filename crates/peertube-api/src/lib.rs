@@ -6,6 +6,7 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod abuse;
 pub mod channels;
 mod comments;
 mod common;
@@ -13,7 +13,11 @@ use time::OffsetDateTime;
 use crate::common::Channel;
 use crate::error::{self, Error};
 use crate::instance::Instance;
-use peertube_ser::{common::VideoState, search, video};
+use peertube_ser::{
+    common::VideoState,
+    search::{self, IdentifiedLabel, Language},
+    video,
+};
 
 #[derive(Clone, Debug)]
 struct File {
@@ -70,10 +74,17 @@ impl From<video::File> for File {
     }
 }
 
+#[derive(Clone, Debug)]
+struct StreamingFile {
+    resolution_id: u64,
+    url: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct StreamingPlaylist {
     id: u64,
     playlist_url: String,
+    files: Vec<StreamingFile>,
 }
 
 #[allow(unused)]
@@ -92,6 +103,14 @@ impl From<video::StreamingPlaylist> for StreamingPlaylist {
         StreamingPlaylist {
             id: v.id,
             playlist_url: v.playlistUrl,
+            files: v
+                .files
+                .into_iter()
+                .map(|f| StreamingFile {
+                    resolution_id: f.resolution.id,
+                    url: f.fileUrl,
+                })
+                .collect(),
         }
     }
 }
@@ -198,6 +217,11 @@ pub struct Video {
     channel: Channel,
     account: Channel,
     state: State,
+    tags: Vec<String>,
+    category: Option<String>,
+    licence: Option<String>,
+    language: Option<String>,
+    thumbnail_path: Option<String>,
 }
 
 #[allow(unused)]
@@ -252,6 +276,45 @@ impl Video {
     pub fn account_display(&self) -> &str {
         &self.account.display_name
     }
+
+    pub fn channel_handle(&self) -> String {
+        format!("{}@{}", self.channel.name, self.channel.host)
+    }
+
+    pub fn account_handle(&self) -> String {
+        format!("{}@{}", self.account.name, self.account.host)
+    }
+
+    /// Only available for videos loaded with `Instance::single_video`
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    pub fn licence(&self) -> Option<&str> {
+        self.licence.as_deref()
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Path of the thumbnail, relative to the instance the video was fetched from
+    pub fn thumbnail_path(&self) -> Option<&str> {
+        self.thumbnail_path.as_deref()
+    }
+}
+
+/// Label of a category or licence, `None` when it isn't set
+fn label(l: IdentifiedLabel) -> Option<String> {
+    l.id.is_some().then_some(l.label)
+}
+
+fn language_label(l: Language) -> Option<String> {
+    l.id.is_some().then_some(l.label)
 }
 
 impl Video {
@@ -273,6 +336,11 @@ impl Video {
             channel: v.channel.into(),
             account: v.account.into(),
             state: v.state.into(),
+            tags: Vec::new(),
+            category: label(v.category),
+            licence: label(v.licence),
+            language: language_label(v.language),
+            thumbnail_path: v.thumbnailPath,
         }
     }
     pub fn from_full(i: &Arc<Instance>, v: video::Video) -> Video {
@@ -296,6 +364,11 @@ impl Video {
             channel: v.channel.into(),
             account: v.account.into(),
             state: v.state.into(),
+            tags: v.tags,
+            category: label(v.category),
+            licence: label(v.licence),
+            language: language_label(v.language),
+            thumbnail_path: Some(v.thumbnailPath),
         }
     }
 
@@ -320,9 +393,23 @@ impl Video {
                 Err(err) => Description::FetchedError(err),
             };
         }
+        if let Description::FetchedError(err) = &*guard {
+            return Err(err.clone());
+        }
         Ok(guard.to_option())
     }
 
+    /// Get the full description if it was already fetched, without waiting for it
+    ///
+    /// Returns `None` while the description is not loaded, `Some(None)` if there is no description
+    /// or it couldn't be fetched
+    pub fn loaded_description(&self) -> Option<Option<String>> {
+        match self.description.try_lock() {
+            Ok(guard) if !guard.is_none() => Some(guard.to_option()),
+            _ => None,
+        }
+    }
+
     fn fetch_description(&self) -> error::Result<Option<String>> {
         self.instance.video_description(self.host(), &self.uuid)
     }
@@ -334,9 +421,13 @@ impl Video {
     pub fn load_description(&self) -> error::Result<()> {
         let mut guard = self.description.lock().unwrap();
         if guard.is_none() {
-            *guard = match self.fetch_description()? {
-                Some(s) => Description::Fetched(s),
-                None => Description::FetchedNone,
+            *guard = match self.fetch_description() {
+                Ok(Some(s)) => Description::Fetched(s),
+                Ok(None) => Description::FetchedNone,
+                Err(err) => {
+                    *guard = Description::FetchedError(err.clone());
+                    return Err(err);
+                }
             };
         }
         Ok(())
@@ -454,6 +545,89 @@ impl Video {
         }
     }
 
+    /// Get the resolution ids of the variants of the first stream
+    pub fn stream_resolutions(&self) -> error::Result<Vec<u64>> {
+        let guard = self.files.lock().unwrap();
+
+        match &*guard {
+            Files::Fetched(_, streams) => Ok(streams
+                .first()
+                .map(|s| s.files.iter().map(|f| f.resolution_id).collect())
+                .unwrap_or_default()),
+            Files::FetchedError(err) => Err(err.clone()),
+            Files::None => panic!("Resolution hasn't been fetched yet"),
+        }
+    }
+
+    /// Get the url of a variant of the first stream
+    pub fn stream_variant_url(&self, id: usize) -> error::Result<String> {
+        let guard = self.files.lock().unwrap();
+
+        match &*guard {
+            Files::Fetched(_, streams) => {
+                let files = streams.first().map(|s| &s.files[..]).unwrap_or_default();
+                files
+                    .get(id)
+                    .map(|f| f.url.clone())
+                    .ok_or(Error::OutOfBound(files.len()))
+            }
+            Files::FetchedError(err) => Err(err.clone()),
+            Files::None => panic!("Resolution hasn't been fetched yet"),
+        }
+    }
+
+    /// Get the url of the audio-only version of the video
+    /// Static files are preferred over the variants of the streams
+    /// Falls back to the lowest resolution when there is no audio-only version
+    pub fn audio_url(&self) -> error::Result<String> {
+        let guard = self.files.lock().unwrap();
+
+        match &*guard {
+            Files::Fetched(files, streams) => {
+                let stream_files = || streams.iter().flat_map(|s| s.files.iter());
+                files
+                    .iter()
+                    .find(|f| f.resoltion_id == 0)
+                    .map(|f| f.download_url.clone())
+                    .or_else(|| {
+                        stream_files()
+                            .find(|f| f.resolution_id == 0)
+                            .map(|f| f.url.clone())
+                    })
+                    .or_else(|| {
+                        files
+                            .iter()
+                            .min_by_key(|f| f.resoltion_id)
+                            .map(|f| f.download_url.clone())
+                    })
+                    .or_else(|| {
+                        stream_files()
+                            .min_by_key(|f| f.resolution_id)
+                            .map(|f| f.url.clone())
+                    })
+                    .ok_or(Error::NoContent)
+            }
+            Files::FetchedError(err) => Err(err.clone()),
+            Files::None => panic!("Resolution hasn't been fetched yet"),
+        }
+    }
+
+    /// Get the torrent url of the audio-only version of the video
+    /// Falls back to the lowest resolution when there is no audio-only version
+    pub fn audio_torrent_url(&self) -> error::Result<String> {
+        let guard = self.files.lock().unwrap();
+
+        match &*guard {
+            Files::Fetched(files, _) => files
+                .iter()
+                .min_by_key(|f| f.resoltion_id)
+                .map(|f| f.torrent_download_url.clone())
+                .ok_or(Error::OutOfBound(0)),
+            Files::FetchedError(err) => Err(err.clone()),
+            Files::None => panic!("Resolution hasn't been fetched yet"),
+        }
+    }
+
     pub fn has_streams(&self) -> error::Result<bool> {
         let guard = self.files.lock().unwrap();
         match &*guard {
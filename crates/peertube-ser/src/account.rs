@@ -0,0 +1,73 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+
+use super::common::Channel;
+
+/// Credentials of the web client of an instance, needed to log in
+#[derive(Deserialize, Debug)]
+pub struct OAuthClient {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Token {
+    pub access_token: String,
+    pub token_type: String,
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct BlockedAccount {
+    pub blockedAccount: Channel,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct BlockedAccounts {
+    pub total: usize,
+    pub data: Vec<BlockedAccount>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Server {
+    pub host: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct BlockedServer {
+    pub blockedServer: Server,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct BlockedServers {
+    pub total: usize,
+    pub data: Vec<BlockedServer>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deser() {
+        let test_data = r#"{"client_id":"v1ikx5hnfop4mdpnci8nsqh93c45rldf","client_secret":"AjWiOapPltI6EnsWQwlFarRtLh4u3dLb"}"#;
+        let _: OAuthClient = serde_json::from_str(test_data).unwrap();
+        let test_data = r#"{"token_type":"Bearer","access_token":"90286a0bdf0f7315d9d3fe8dabf9e1d2be9c97d0","refresh_token":"2e0d675df9fc96d2e4ec8a3ebbbf45eca9137bb7","expires_in":86399,"refresh_token_expires_in":1209599}"#;
+        let _: Token = serde_json::from_str(test_data).unwrap();
+        let test_data = r#"{"total":1,"data":[{"byAccount":{"url":"https://instance/accounts/me","name":"me","host":"instance","avatar":null,"id":1,"displayName":"Me"},"blockedAccount":{"url":"https://other/accounts/spammer","name":"spammer","host":"other","avatar":null,"id":2,"displayName":"Spammer"},"createdAt":"2023-10-16T10:00:00.000Z"}]}"#;
+        let _: BlockedAccounts = serde_json::from_str(test_data).unwrap();
+        let test_data = r#"{"total":1,"data":[{"byAccount":{"url":"https://instance/accounts/me","name":"me","host":"instance","avatar":null,"id":1,"displayName":"Me"},"blockedServer":{"host":"spam.example.com"},"createdAt":"2023-10-16T10:00:00.000Z"}]}"#;
+        let _: BlockedServers = serde_json::from_str(test_data).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+
+use super::search::Language;
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct Caption {
+    pub language: Language,
+    pub captionPath: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct Captions {
+    pub total: usize,
+    pub data: Vec<Caption>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deser() {
+        let test_data = r#"{"total":1,"data":[{"language":{"id":"en","label":"English"},"captionPath":"/lazy-static/video-captions/0c1f1b5a-en.vtt","updatedAt":"2021-02-02T10:09:28.547Z"}]}"#;
+        let _: Captions = serde_json::from_str(test_data).unwrap();
+    }
+}
@@ -24,6 +24,8 @@ pub struct Comment {
     pub createdAt: OffsetDateTime,
     pub deleted: Option<String>,
     pub isDeleted: bool,
+    #[serde(default)]
+    pub totalReplies: u64,
     pub account: Option<Channel>,
 }
 
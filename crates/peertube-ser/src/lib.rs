@@ -6,12 +6,15 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
+pub mod account;
+pub mod captions;
 pub mod channels;
 pub mod comments;
 pub mod common;
 pub mod search;
 pub mod video;
 
+pub use captions::Captions;
 pub use channels::Channels;
 pub use comments::Comments;
 pub use search::Videos;
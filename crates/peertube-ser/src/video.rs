@@ -51,6 +51,8 @@ pub struct Video {
     pub licence: IdentifiedLabel,
     pub language: Language,
     pub privacy: IdentifiedLabel,
+    #[serde(default)]
+    pub tags: Vec<String>,
 
     /// The list of files for the video
     /// Each file corresponds to an available resolution
@@ -79,9 +81,22 @@ pub struct File {
     pub fileDownloadUrl: String,
 }
 
+/// File of a streaming playlist
+/// Each file corresponds to a variant of the stream
+#[derive(Deserialize, Debug)]
+#[allow(non_snake_case)]
+pub struct StreamingFile {
+    pub resolution: Resolution,
+    #[serde(default)]
+    pub size: u64,
+    pub fileUrl: String,
+}
+
 #[derive(Deserialize, Debug)]
 #[allow(non_snake_case)]
 pub struct StreamingPlaylist {
     pub id: u64,
     pub playlistUrl: String,
+    #[serde(default)]
+    pub files: Vec<StreamingFile>,
 }
@@ -9,50 +9,80 @@
 mod clap_app;
 mod config;
 mod display;
+mod downloads;
 mod history;
+mod hooks;
 mod input;
+mod lists;
+mod mpv;
 mod parser;
+mod players;
 mod preloadables;
+mod queue;
+mod resolution;
+mod stats;
+mod template;
+mod tui;
 
 pub use config::ConfigLoadError;
-use config::{Blocklist, Config, InitialInfo};
-use display::Display;
+use config::{BlockKind, Blocklist, Config, InitialInfo, PRIVATE_USER_AGENT};
+use display::{pretty_duration, Display};
+use downloads::DownloadManager;
 use history::History;
+use hooks::Event;
 use input::Editor;
-use parser::ParsedQuery;
+use lists::{Lists, SavedVideo};
+use parser::{parse_ids, parse_time_range, ParsedQuery};
+use players::Players;
 
 use crate::error::Error;
 
 use rustyline::error::ReadlineError;
 
+use peertube_api::abuse::{Reported, PREDEFINED_REASONS};
 use peertube_api::{error::Error as ApiError, Instance, VideoState};
 
 use preloadable_list::PreloadableList;
-use preloadables::{Channels, Comments, Videos};
+use preloadables::{Channels, Comments, SavedList, Videos};
+use resolution::ResolutionPolicy;
 
-use std::fs::create_dir;
+use std::error::Error as _;
+use std::fs::{create_dir, create_dir_all, remove_file};
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use directories::ProjectDirs;
+use directories::{ProjectDirs, UserDirs};
 use std::process::Command;
+use time::UtcOffset;
 
 const SEARCH_TOTAL: usize = 20;
 
 pub struct Cli {
     config: Config,
     history: History,
+    /// Bookmarks and local playlists
+    lists: Lists,
     dirs: Option<ProjectDirs>,
     rl: Editor,
     display: Display,
     instance: Arc<Instance>,
+    downloads: DownloadManager,
     initial_info: InitialInfo,
+
+    /// Videos waiting to be played with :play-queue
+    queue: Vec<Arc<peertube_api::Video>>,
+    autoplay: bool,
+    /// The history is not saved when quitting in private mode
+    private: bool,
+    players: Players,
 }
 
 impl Cli {
     /// Loads an instance of the cli
     pub fn init() -> Result<Cli, Error> {
         let (config, initial_info, load_errors) = Config::new();
-        let display = Display::new(config.colors());
+        let display = Display::new(config.colors(), config.layouts(), config.theme());
 
         let mut err_iter = load_errors.into_iter();
         if let Some(err) = err_iter.next() {
@@ -67,20 +97,41 @@ impl Cli {
         let mut history = History::new();
 
         let dirs = ProjectDirs::from("", "peertube-viewer-rs", "peertube-viewer-rs");
-        let mut rl = Editor::new(config.edit_mode(), config.colors())?;
+        // The editor is kept for the whole session, so the names can be leaked
+        let customs: Vec<&'static str> = config
+            .custom_commands()
+            .iter()
+            .map(|c| &*Box::leak(format!(":{}", c.name).into_boxed_str()))
+            .collect();
+        let mut rl = Editor::new(
+            config.edit_mode(),
+            config.colors(),
+            config.theme(),
+            customs.leak(),
+        )?;
 
         // Loads the history if available
         if let Some(d) = dirs.as_ref() {
             let cache = d.cache_dir();
 
-            create_dir(cache).unwrap_or(());
+            if !config.private() {
+                create_dir(cache).unwrap_or(());
+            }
 
             let mut view_hist_file = cache.to_owned();
-            view_hist_file.push("history");
+            view_hist_file.push("history.toml");
+            if !view_hist_file.exists() {
+                // Migrate the history from the format with one uuid per line
+                view_hist_file.set_file_name("history");
+            }
             let mut cmd_hist_file = cache.to_owned();
             cmd_hist_file.push("cmd_history");
 
+            let mut positions_file = cache.to_owned();
+            positions_file.push("positions");
+
             history.load_file(&view_hist_file).unwrap_or(()); // unwrap_or to ignore the unused_must_use warnings
+            history.load_positions(&positions_file).unwrap_or(());
             rl.load_history(&cmd_hist_file).unwrap_or(()); // we don't care if the loading failed
         }
 
@@ -102,19 +153,84 @@ impl Cli {
             config.is_search_engine(),
         );
 
-        if !matches!(initial_info, InitialInfo::VideoUrl(_)) {
+        if !matches!(initial_info, InitialInfo::VideoUrl(_) | InitialInfo::Stats) {
             display.welcome(instance.host());
+            for (path, count) in config.blocklist_files() {
+                display.info(&format!(
+                    "Loaded {count} blocked entries from {}",
+                    path.display()
+                ));
+            }
+        }
+
+        let download_dir = match config.download_dir() {
+            Some(d) => d.to_owned(),
+            None => UserDirs::new()
+                .and_then(|d| d.download_dir().map(|p| p.to_owned()))
+                .unwrap_or_else(|| PathBuf::from(".")),
+        };
+        let downloads = DownloadManager::new(
+            instance.clone(),
+            download_dir,
+            config.download_parallel(),
+            config.resolution_policy(),
+            config.sidecars(),
+            config.hooks().clone(),
+        );
+
+        let mut lists = Lists::new();
+
+        // Resume the downloads from the previous session
+        if let Some(d) = dirs.as_ref() {
+            let mut lists_file = d.data_dir().to_owned();
+            lists_file.push("lists.toml");
+            lists.load_file(&lists_file).unwrap_or(());
+
+            let mut downloads_file = d.data_dir().to_owned();
+            downloads_file.push("downloads.toml");
+            if let Ok(resumed) = downloads.load_file(&downloads_file) {
+                if resumed > 0 {
+                    display.info(&format!(
+                        "Resuming {resumed} download(s) from the previous session (:downloads to see them)"
+                    ));
+                }
+            }
         }
 
-        Ok(Cli {
+        let mut cli = Cli {
             config,
             history,
+            lists,
             dirs,
             rl,
             display,
             instance,
+            downloads,
             initial_info,
-        })
+            queue: Vec::new(),
+            autoplay: false,
+            private: false,
+            players: Players::new(),
+        };
+        if cli.config.private() {
+            cli.set_private(true);
+        } else {
+            cli.login();
+        }
+        Ok(cli)
+    }
+
+    /// In private mode, the history is not saved, the hooks are not run and a generic user agent is used
+    fn set_private(&mut self, private: bool) {
+        self.private = private;
+        self.history.set_private(private);
+        self.rl.set_private(private);
+        self.downloads.set_private(private);
+        self.instance.set_user_agent(if private {
+            Some(PRIVATE_USER_AGENT.into())
+        } else {
+            self.config.user_agent()
+        });
     }
 
     /// Main loop for he cli interface
@@ -122,13 +238,17 @@ impl Cli {
         let mode = Mode::Temp; //Placeholder that will be changed just after anyway on the first loop run
         let action = match self.initial_info.take() {
             InitialInfo::VideoUrl(s) => {
-                self.play_vid(&self.instance.single_video(self.instance.host(), &s)?)?;
+                self.play_vid(&self.instance.single_video(self.instance.host(), &s)?, None)?;
                 return Ok(());
             }
             InitialInfo::Query(s) => ParsedQuery::Query(s),
             InitialInfo::Channels(s) => ParsedQuery::Channels(s),
             InitialInfo::Handle(s) => ParsedQuery::Chandle(s),
             InitialInfo::Trending => ParsedQuery::Trending,
+            InitialInfo::Stats => {
+                self.show_stats();
+                return Ok(());
+            }
             InitialInfo::None => {
                 self.display.info("Search for videos (:h for help)");
                 self.rl.first_readline(">> ".to_string())?
@@ -146,9 +266,11 @@ impl Cli {
         // Main loop
         while !data.stop {
             if let Err(err) = self.one_loop(&mut data) {
+                let message = error_message(&err);
                 if let Some(err) = self.handle_err(err) {
                     return Err(err);
                 } else {
+                    self.error_hook(message);
                     data.changed_action = true;
                     data.mode = Mode::Temp;
                     self.display.continue_despite_error();
@@ -167,6 +289,10 @@ impl Cli {
 
         data.mode.ensure_init()?;
 
+        for msg in self.players.poll() {
+            self.display.warn(&msg);
+        }
+
         match &mut data.mode {
             Mode::Videos(videos) => {
                 self.video_prompt(videos, &mut data.action, &mut data.changed_action)?
@@ -177,6 +303,7 @@ impl Cli {
             Mode::Comments(comments) => {
                 self.comments_prompt(comments, &mut data.action, &mut data.changed_action)?
             }
+            Mode::Downloads => self.downloads_prompt(&mut data.action, &mut data.changed_action)?,
             Mode::Temp => unreachable!(),
         };
         Ok(())
@@ -207,6 +334,7 @@ impl Cli {
                     Mode::Comments(comments) => {
                         comments.try_next()?;
                     }
+                    Mode::Downloads => {}
                     Mode::Temp => unreachable!(),
                 },
                 ParsedQuery::Previous => match &mut data.mode {
@@ -219,6 +347,7 @@ impl Cli {
                     Mode::Comments(comments) => {
                         comments.prev();
                     }
+                    Mode::Downloads => {}
                     Mode::Temp => unreachable!(),
                 },
                 ParsedQuery::Trending => {
@@ -240,8 +369,19 @@ impl Cli {
                     }
                 }
                 ParsedQuery::Help => {
-                    self.display.help();
+                    self.display.help(self.config.custom_commands());
+                    self.rl.std_in("Press enter to continue".to_string())?;
+                    if data.mode.is_temp() {
+                        self.display.info("Search for videos (:h for help)");
+                        data.action = self.rl.first_readline(">> ".to_string())?;
+                        data.changed_action = true;
+                        self.parse_action(data)?;
+                    }
+                }
+                ParsedQuery::Stats => {
+                    self.show_stats();
                     self.rl.std_in("Press enter to continue".to_string())?;
+                    self.rl.add_history_entry(":stats");
                     if data.mode.is_temp() {
                         self.display.info("Search for videos (:h for help)");
                         data.action = self.rl.first_readline(">> ".to_string())?;
@@ -275,30 +415,587 @@ impl Cli {
                     self.comments(&mut data.mode, *id);
                     self.rl.add_history_entry(&format!(":comments {id}"));
                 }
+                ParsedQuery::Download(ids) => {
+                    self.download(&data.mode, ids);
+                }
+                ParsedQuery::Downloads => {
+                    data.mode = Mode::Downloads;
+                    self.rl.add_history_entry(":downloads");
+                }
+                ParsedQuery::Cancel(id) => {
+                    if *id == 0 || !self.downloads.cancel(id - 1) {
+                        self.display
+                            .err(&format!("Download {id} can't be cancelled"));
+                    }
+                    self.rl.add_history_entry(&format!(":cancel {id}"));
+                }
+                ParsedQuery::Retry(id) => {
+                    if *id == 0 || !self.downloads.retry(id - 1) {
+                        self.display.err(&format!("Download {id} can't be retried"));
+                    }
+                    self.rl.add_history_entry(&format!(":retry {id}"));
+                }
+                ParsedQuery::Play(id, resolution) => {
+                    if let Mode::Videos(videos) = &data.mode {
+                        let video = videos.current()[id - 1].clone();
+                        self.play_vid(&video, *resolution)?;
+                    } else {
+                        self.display.err(&"Only videos can be played with :play");
+                    }
+                    match resolution {
+                        Some(r) => self.rl.add_history_entry(&format!(":play {id} {r}p")),
+                        None => self.rl.add_history_entry(&format!(":play {id}")),
+                    }
+                }
+                ParsedQuery::PlayWith(profile, id) => {
+                    if !self.config.has_profile(profile) {
+                        self.display.err(&format!(
+                            "Unknown player profile \"{profile}\"\nAvailable profiles: {}",
+                            self.config.profile_names().join(", ")
+                        ));
+                    } else if let Mode::Videos(videos) = &data.mode {
+                        let video = videos.current()[id - 1].clone();
+                        self.play_vid_with(&video, None, Some(profile))?;
+                    } else {
+                        self.display
+                            .err(&"Only videos can be played with :play-with");
+                    }
+                    self.rl
+                        .add_history_entry(&format!(":play-with {profile} {id}"));
+                }
+                ParsedQuery::Queue(ids) if ids.is_empty() => {
+                    self.show_queue()?;
+                    self.rl.add_history_entry(":queue");
+                }
+                ParsedQuery::Queue(ids) => {
+                    if let Mode::Videos(videos) = &data.mode {
+                        self.queue
+                            .extend(ids.iter().map(|id| videos.current()[id - 1].clone()));
+                        self.display.info(&format!(
+                            "{} video(s) in the queue (:play-queue to play them)",
+                            self.queue.len()
+                        ));
+                    } else {
+                        self.display.err(&"Only videos can be added to the queue");
+                    }
+                    let ids_str: Vec<_> = ids.iter().map(|id| id.to_string()).collect();
+                    self.rl
+                        .add_history_entry(&format!(":queue {}", ids_str.join(" ")));
+                }
+                ParsedQuery::PlayQueue => {
+                    self.play_queue()?;
+                    self.rl.add_history_entry(":play-queue");
+                }
+                ParsedQuery::Private(on) => {
+                    self.set_private(*on);
+                    if *on {
+                        self.display
+                            .info("Private mode enabled, the history won't be saved when quitting");
+                    } else {
+                        self.display.info("Private mode disabled");
+                        self.rl.add_history_entry(":private off");
+                    }
+                    if data.mode.is_temp() {
+                        self.display.info("Search for videos (:h for help)");
+                        data.action = self.rl.first_readline(">> ".to_string())?;
+                        data.changed_action = true;
+                        self.parse_action(data)?;
+                    }
+                }
+                ParsedQuery::Autoplay(on) => {
+                    self.autoplay = *on;
+                    if *on {
+                        self.display.info("Autoplay enabled");
+                        self.rl.add_history_entry(":autoplay on");
+                    } else {
+                        self.display.info("Autoplay disabled");
+                        self.rl.add_history_entry(":autoplay off");
+                    }
+                }
+                ParsedQuery::Players => {
+                    let players = self.players.list();
+                    self.display.player_list(&players);
+                    self.display.info(&format!(
+                        "{} player(s) running in the background (:kill <id> to stop one)",
+                        players.len()
+                    ));
+                    self.rl.std_in("Press enter to continue".to_string())?;
+                    self.rl.add_history_entry(":players");
+                }
+                ParsedQuery::Kill(id) => {
+                    if *id == 0 || !self.players.kill(id - 1).map_err(Error::VideoLaunch)? {
+                        self.display.err(&format!("Player {id} isn't running"));
+                    }
+                    self.rl.add_history_entry(&format!(":kill {id}"));
+                }
+                ParsedQuery::History(query) => {
+                    let videos: Vec<_> = self
+                        .history
+                        .entries(query)
+                        .into_iter()
+                        .map(|e| (e.host.clone(), e.uuid.clone()))
+                        .collect();
+                    if query.is_empty() {
+                        self.rl.add_history_entry(":history");
+                    } else {
+                        self.rl.add_history_entry(&format!(":history {query}"));
+                    }
+                    let list = SavedList::History(query.clone());
+                    self.open_saved(data, list, videos, "No video of the history matches")?;
+                }
+                ParsedQuery::Forget(id) => {
+                    let id = *id;
+                    let mut refresh = None;
+                    if let Mode::Videos(videos) = &data.mode {
+                        let video = &videos.current()[id - 1];
+                        if self.history.forget(video.uuid()) {
+                            self.display.info(&format!(
+                                "\"{}\" was removed from the history",
+                                video.name()
+                            ));
+                            if let Some(SavedList::History(query)) = videos.loader().saved_list() {
+                                refresh = Some(query.clone());
+                            }
+                        } else {
+                            self.display.err(&"This video isn't in the history");
+                        }
+                    } else {
+                        self.display
+                            .err(&"Only videos can be removed from the history");
+                    }
+                    self.rl.add_history_entry(&format!(":forget {id}"));
+
+                    // Refresh the history so that the video isn't listed anymore
+                    if let Some(query) = refresh {
+                        data.action = ParsedQuery::History(query);
+                        self.parse_action(data)?;
+                    }
+                }
+                ParsedQuery::ClearHistory => {
+                    let confirm = self
+                        .rl
+                        .std_in("Clear the whole viewing history? [y/N]: ".to_string())?;
+                    if confirm.trim() == "y" || confirm.trim() == "Y" {
+                        self.history.clear();
+                        self.display.info("The history was cleared");
+                    }
+                    self.rl.add_history_entry(":clear-history");
+                }
+                ParsedQuery::Bookmark(id) => {
+                    let id = *id;
+                    let mut refresh = false;
+                    if let Mode::Videos(videos) = &data.mode {
+                        let video = &videos.current()[id - 1];
+                        if self.lists.toggle_bookmark(SavedVideo::new(video)) {
+                            self.display
+                                .info(&format!("\"{}\" was bookmarked", video.name()));
+                        } else {
+                            self.display.info(&format!(
+                                "\"{}\" was removed from the bookmarks",
+                                video.name()
+                            ));
+                            refresh = videos.loader().saved_list() == Some(&SavedList::Bookmarks);
+                        }
+                    } else {
+                        self.display.err(&"Only videos can be bookmarked");
+                    }
+                    self.rl.add_history_entry(&format!(":bookmark {id}"));
+
+                    if refresh {
+                        data.action = ParsedQuery::Bookmarks;
+                        self.parse_action(data)?;
+                    }
+                }
+                ParsedQuery::Block(kind, id) => {
+                    let (kind, id) = (*kind, *id);
+                    self.block(&data.mode, kind, id);
+                    self.rl
+                        .add_history_entry(&format!(":block-{} {id}", kind.name()));
+                }
+                ParsedQuery::Unblock(entry) => {
+                    let entry = entry.clone();
+                    if self.config.unblock(&entry) {
+                        self.display.info(&format!("{entry} was unblocked"));
+                        self.save_blocklists();
+                    } else if self.config.in_blocklist_files(&entry) {
+                        self.display.err(&format!(
+                            "{entry} is blocked by a blocklist file, remove it from the file to unblock it"
+                        ));
+                    } else {
+                        self.display.err(&format!("{entry} isn't blocked"));
+                    }
+                    self.rl.add_history_entry(&format!(":unblock {entry}"));
+                }
+                ParsedQuery::Mute(kind, id) => {
+                    let (kind, id) = (*kind, *id);
+                    self.mute(&data.mode, kind, id);
+                    self.rl
+                        .add_history_entry(&format!(":mute-{} {id}", kind.name()));
+                }
+                ParsedQuery::Unmute(entry) => {
+                    let entry = entry.clone();
+                    let res = if entry.contains('@') {
+                        self.instance.unmute_account(&entry)
+                    } else {
+                        self.instance.unmute_instance(&entry)
+                    };
+                    match res {
+                        Ok(()) => {
+                            self.config.unmute(&entry);
+                            self.display.info(&format!("{entry} was unmuted"));
+                        }
+                        Err(e) => self.display.err(&e),
+                    }
+                    self.rl.add_history_entry(&format!(":unmute {entry}"));
+                }
+                ParsedQuery::Report(id, reason) => {
+                    let (id, reason) = (*id, reason.clone());
+                    self.report(&data.mode, id, &reason)?;
+                    self.rl.add_history_entry(&format!(":report {id} {reason}"));
+                }
+                ParsedQuery::Mutes => {
+                    if self.instance.is_logged_in() {
+                        self.display.blocklists(&self.config.muted());
+                        self.rl.std_in("Press enter to continue".to_string())?;
+                    } else {
+                        self.display.err(&ApiError::NotLoggedIn);
+                    }
+                    self.rl.add_history_entry(":mutes");
+                }
+                ParsedQuery::Blocklist => {
+                    self.display.blocklists(&self.config.blocklists());
+                    self.rl.std_in("Press enter to continue".to_string())?;
+                    self.rl.add_history_entry(":blocklist");
+                }
+                ParsedQuery::Bookmarks => {
+                    self.rl.add_history_entry(":bookmarks");
+                    let videos = saved_ids(self.lists.bookmarks());
+                    self.open_saved(data, SavedList::Bookmarks, videos, "There is no bookmark")?;
+                }
+                ParsedQuery::PlaylistAdd(name, id) => {
+                    let (name, id) = (name.clone(), *id);
+                    if let Mode::Videos(videos) = &data.mode {
+                        let video = &videos.current()[id - 1];
+                        if self.lists.add_to_playlist(&name, SavedVideo::new(video)) {
+                            self.display.info(&format!(
+                                "\"{}\" was added to the playlist {name}",
+                                video.name()
+                            ));
+                        } else {
+                            self.display
+                                .err(&format!("This video is already in the playlist {name}"));
+                        }
+                    } else {
+                        self.display.err(&"Only videos can be added to a playlist");
+                    }
+                    self.rl
+                        .add_history_entry(&format!(":playlist-add {name} {id}"));
+                }
+                ParsedQuery::Playlist(name) => {
+                    let name = name.clone();
+                    if name.is_empty() {
+                        self.rl.add_history_entry(":playlist");
+                        let playlists = self.lists.playlists();
+                        if playlists.is_empty() {
+                            self.display.warn(
+                                &"There is no playlist (:playlist-add <name> <ID> to create one)",
+                            );
+                        } else {
+                            self.display.playlist_list(&playlists);
+                            self.rl.std_in("Press enter to continue".to_string())?;
+                        }
+                        if data.mode.is_temp() {
+                            self.display.info("Search for videos (:h for help)");
+                            data.action = self.rl.first_readline(">> ".to_string())?;
+                            data.changed_action = true;
+                            self.parse_action(data)?;
+                        }
+                    } else {
+                        self.rl.add_history_entry(&format!(":playlist {name}"));
+                        let (videos, empty) = match self.lists.playlist(&name) {
+                            Some(videos) => (saved_ids(videos), "This playlist is empty".into()),
+                            None => (Vec::new(), format!("There is no playlist named {name}")),
+                        };
+                        self.open_saved(data, SavedList::Playlist(name), videos, &empty)?;
+                    }
+                }
+                ParsedQuery::PlaylistRm(name, id) => {
+                    let (name, id) = (name.clone(), *id);
+                    let mut refresh = false;
+                    match id {
+                        Some(id) => {
+                            if let Mode::Videos(videos) = &data.mode {
+                                let video = &videos.current()[id - 1];
+                                if self.lists.remove_from_playlist(&name, video.uuid()) {
+                                    self.display.info(&format!(
+                                        "\"{}\" was removed from the playlist {name}",
+                                        video.name()
+                                    ));
+                                    refresh = videos.loader().saved_list()
+                                        == Some(&SavedList::Playlist(name.clone()));
+                                } else {
+                                    self.display
+                                        .err(&format!("This video isn't in the playlist {name}"));
+                                }
+                            } else {
+                                self.display
+                                    .err(&"Only videos can be removed from a playlist");
+                            }
+                            self.rl
+                                .add_history_entry(&format!(":playlist-rm {name} {id}"));
+                        }
+                        None => {
+                            if self.lists.playlist(&name).is_none() {
+                                self.display
+                                    .err(&format!("There is no playlist named {name}"));
+                            } else {
+                                let confirm = self
+                                    .rl
+                                    .std_in(format!("Delete the playlist {name}? [y/N]: "))?;
+                                if confirm.trim() == "y" || confirm.trim() == "Y" {
+                                    self.lists.remove_playlist(&name);
+                                    self.display
+                                        .info(&format!("The playlist {name} was deleted"));
+                                }
+                            }
+                            self.rl.add_history_entry(&format!(":playlist-rm {name}"));
+                        }
+                    }
+
+                    // Refresh the playlist so that the video isn't listed anymore
+                    if refresh {
+                        data.action = ParsedQuery::Playlist(name);
+                        self.parse_action(data)?;
+                    }
+                }
+                ParsedQuery::Custom(name, id) => {
+                    self.custom_command(&data.mode, name, *id)?;
+                    self.rl.add_history_entry(&format!(":{name} {id}"));
+                }
                 ParsedQuery::Id(_) => unreachable!(),
             };
         }
         Ok(())
     }
 
+    /// Instance, channel or account of an item of the list, `action` is used in the error message
+    fn target(&self, mode: &Mode, kind: BlockKind, id: usize, action: &str) -> Option<String> {
+        let name = match (kind, mode) {
+            (BlockKind::Instance, Mode::Videos(v)) => v.current()[id - 1].host().to_owned(),
+            (BlockKind::Instance, Mode::Channels(c)) => c.current()[id - 1].host().to_owned(),
+            (BlockKind::Instance, Mode::Comments(c)) => {
+                c.current()[id - 1].author_host().to_owned()
+            }
+            (BlockKind::Channel, Mode::Videos(v)) => v.current()[id - 1].channel_handle(),
+            (BlockKind::Channel, Mode::Channels(c)) => c.current()[id - 1].handle(),
+            (BlockKind::Account, Mode::Videos(v)) => v.current()[id - 1].account_handle(),
+            (BlockKind::Account, Mode::Comments(c)) => c.current()[id - 1].author_handle(),
+            _ => {
+                self.display
+                    .err(&format!("There is no {} to {action} here", kind.name()));
+                return None;
+            }
+        };
+        Some(name)
+    }
+
+    /// Block the instance, the channel or the account of an item of the list
+    fn block(&mut self, mode: &Mode, kind: BlockKind, id: usize) {
+        let Some(name) = self.target(mode, kind, id, "block") else {
+            return;
+        };
+
+        if self.config.block(kind, name.clone()) {
+            self.display
+                .info(&format!("The {} {name} was blocked", kind.name()));
+            self.save_blocklists();
+        } else {
+            self.display.err(&format!("{name} is already blocked"));
+        }
+    }
+
+    /// Mute the instance or the account of an item on the account of the user
+    fn mute(&mut self, mode: &Mode, kind: BlockKind, id: usize) {
+        let Some(name) = self.target(mode, kind, id, "mute") else {
+            return;
+        };
+
+        let res = match kind {
+            BlockKind::Instance => self.instance.mute_instance(&name),
+            BlockKind::Channel | BlockKind::Account => self.instance.mute_account(&name),
+        };
+        match res {
+            Ok(()) => {
+                self.config.mute(kind, name.clone());
+                self.display
+                    .info(&format!("The {} {name} was muted", kind.name()));
+            }
+            Err(e) => self.display.err(&e),
+        }
+    }
+
+    /// Report a video or a comment, asking for the predefined reasons and the part of the video
+    fn report(&mut self, mode: &Mode, id: usize, reason: &str) -> Result<(), Error> {
+        let mut reported = match mode {
+            Mode::Videos(v) => Reported::Video {
+                uuid: v.current()[id - 1].uuid().to_owned(),
+                range: None,
+            },
+            Mode::Comments(c) => Reported::Comment(c.current()[id - 1].id()),
+            _ => {
+                self.display
+                    .err(&"Only videos and comments can be reported");
+                return Ok(());
+            }
+        };
+        if !self.instance.is_logged_in() {
+            self.display.err(&ApiError::NotLoggedIn);
+            return Ok(());
+        }
+
+        self.display
+            .report_reasons(&PREDEFINED_REASONS.map(|(_, description)| description));
+        let choice = self
+            .rl
+            .std_in("Reasons (ex: 1 3, empty for none): ".to_string())?;
+        let predefined: Vec<&str> = match parse_ids(choice.trim()) {
+            Ok(ids)
+                if ids
+                    .iter()
+                    .all(|id| (1..=PREDEFINED_REASONS.len()).contains(id)) =>
+            {
+                ids.iter().map(|id| PREDEFINED_REASONS[id - 1].0).collect()
+            }
+            _ => {
+                self.display
+                    .err(&"Invalid reasons, the report was cancelled");
+                return Ok(());
+            }
+        };
+
+        if let Reported::Video { range, .. } = &mut reported {
+            let part = self.rl.std_in(
+                "Part of the video (ex: 1:20-2:05, empty for the whole video): ".to_string(),
+            )?;
+            if !part.trim().is_empty() {
+                match parse_time_range(part.trim()) {
+                    Ok(r) => *range = Some(r),
+                    Err(_) => {
+                        self.display
+                            .err(&"Invalid part of the video, the report was cancelled");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        match self.instance.report(&reported, reason, &predefined) {
+            Ok(()) => self.display.info("The report was sent to the moderators"),
+            Err(e) => self.display.err(&e),
+        }
+        Ok(())
+    }
+
+    /// Log in with the account from the config and load its mute lists
+    fn login(&mut self) {
+        let Some(account) = self.config.account() else {
+            return;
+        };
+        if self.config.is_search_engine() {
+            self.display
+                .err(&"Logging in requires a main instance, not a search engine");
+            return;
+        }
+        let username = account.username.clone();
+        let password = match account.password() {
+            Ok(password) => password,
+            Err(e) => {
+                self.display
+                    .err(&format!("Unable to get the password of {username}: {e}"));
+                return;
+            }
+        };
+        let muted = self.instance.login(&username, &password).and_then(|()| {
+            Ok((
+                self.instance.muted_instances()?,
+                self.instance.muted_accounts()?,
+            ))
+        });
+        match muted {
+            Ok((instances, accounts)) => {
+                self.display.info(&format!(
+                    "Logged in as {username}, {} instance(s) and {} account(s) muted",
+                    instances.len(),
+                    accounts.len()
+                ));
+                self.config.set_muted(instances, accounts);
+            }
+            Err(e) => self
+                .display
+                .err(&format!("Unable to log in as {username}: {e}")),
+        }
+    }
+
+    fn save_blocklists(&self) {
+        if let Err(e) = self.config.save_blocklists() {
+            self.display.err(&format!(
+                "Unable to save the blocklist in the config file: {e}"
+            ));
+        }
+    }
+
+    /// Browse the videos of a local list, or show `empty` if there are none
+    fn open_saved(
+        &mut self,
+        data: &mut LoopData,
+        list: SavedList,
+        videos: Vec<(String, String)>,
+        empty: &str,
+    ) -> Result<(), Error> {
+        if !videos.is_empty() {
+            let saved_tmp = PreloadableList::new(
+                Videos::new_saved(self.instance.clone(), list, videos),
+                SEARCH_TOTAL,
+            );
+            data.mode = Mode::Videos(saved_tmp);
+        } else {
+            self.display.warn(&empty);
+            if data.mode.is_temp() {
+                self.display.info("Search for videos (:h for help)");
+                data.action = self.rl.first_readline(">> ".to_string())?;
+                data.changed_action = true;
+                self.parse_action(data)?;
+            }
+        }
+        Ok(())
+    }
+
     fn video_prompt(
         &mut self,
         videos: &mut PreloadableList<Videos>,
         action: &mut ParsedQuery,
         changed_action: &mut bool,
     ) -> Result<(), Error> {
-        self.display
-            .video_list(videos.current(), &self.history, &self.config);
-        self.display.mode_info(
-            videos.loader().name(),
-            videos.expected_total(),
-            videos.offset(),
-            videos.current_len(),
+        let render = |videos: &PreloadableList<Videos>| {
+            self.display
+                .render_video_list(videos.current(), &self.history, &self.config)
+                + &self.display.render_mode_info(
+                    videos.loader().name(),
+                    videos.expected_total(),
+                    videos.offset(),
+                    videos.current_len(),
+                )
+        };
+        print!("{}", render(videos));
+        videos.loader().preload_res(
+            self.config.select_quality()
+                || self.config.use_raw_url()
+                || self.config.resolution_policy().is_set(),
         );
-        videos
-            .loader()
-            .preload_res(self.config.select_quality() || self.config.use_raw_url());
-        let choice = match self.rl.autoload_readline(">> ".to_string(), videos)? {
+        let choice = match self
+            .rl
+            .autoload_readline(">> ".to_string(), videos, render)?
+        {
             ParsedQuery::Id(id) => id,
             new_action => {
                 *action = new_action;
@@ -308,7 +1005,22 @@ impl Cli {
         };
 
         let video = videos.current()[choice - 1].clone();
-        self.play_vid(&video)?;
+        let mut progress = self.play_vid(&video, None)?;
+        let mut idx = choice - 1;
+        while self.autoplay {
+            idx += 1;
+            if idx >= videos.current_len() {
+                if videos.try_next()?.is_empty() {
+                    break;
+                }
+                idx = 0;
+            }
+            let next = videos.current()[idx].clone();
+            if !self.continue_with(progress, &next)? {
+                break;
+            }
+            progress = self.play_vid(&next, None)?;
+        }
         *changed_action = false;
         Ok(())
     }
@@ -319,15 +1031,21 @@ impl Cli {
         action: &mut ParsedQuery,
         changed_action: &mut bool,
     ) -> Result<(), Error> {
-        self.display
-            .channel_list(channels.current(), &self.history, &self.config);
-        self.display.mode_info(
-            "Channel search",
-            channels.expected_total(),
-            channels.offset(),
-            channels.current_len(),
-        );
-        match self.rl.autoload_readline(">> ".to_string(), channels)? {
+        let render = |channels: &PreloadableList<Channels>| {
+            self.display
+                .render_channel_list(channels.current(), &self.config)
+                + &self.display.render_mode_info(
+                    "Channel search",
+                    channels.expected_total(),
+                    channels.offset(),
+                    channels.current_len(),
+                )
+        };
+        print!("{}", render(channels));
+        match self
+            .rl
+            .autoload_readline(">> ".to_string(), channels, render)?
+        {
             ParsedQuery::Id(id) => {
                 *action = ParsedQuery::Chandle(channels.current()[id - 1].handle());
                 *changed_action = true;
@@ -347,14 +1065,21 @@ impl Cli {
         action: &mut ParsedQuery,
         changed_action: &mut bool,
     ) -> Result<(), Error> {
-        self.display.comment_list(comments.current());
-        self.display.mode_info(
-            "Browsing video comments",
-            comments.expected_total(),
-            comments.offset(),
-            comments.current_len(),
-        );
-        match self.rl.autoload_readline(">> ".to_string(), comments)? {
+        let render = |comments: &PreloadableList<Comments>| {
+            self.display
+                .render_comment_list(comments.current(), &self.config)
+                + &self.display.render_mode_info(
+                    "Browsing video comments",
+                    comments.expected_total(),
+                    comments.offset(),
+                    comments.current_len(),
+                )
+        };
+        print!("{}", render(comments));
+        match self
+            .rl
+            .autoload_readline(">> ".to_string(), comments, render)?
+        {
             ParsedQuery::Id(id) => {
                 *action = ParsedQuery::Browser(id);
                 *changed_action = true;
@@ -368,6 +1093,62 @@ impl Cli {
         }
     }
 
+    fn downloads_prompt(
+        &mut self,
+        action: &mut ParsedQuery,
+        changed_action: &mut bool,
+    ) -> Result<(), Error> {
+        let jobs = self.downloads.jobs();
+        self.display.download_list(&jobs);
+        self.display.info(&format!(
+            "{} download(s) (:downloads to refresh, :h for help)",
+            jobs.len()
+        ));
+        match self.rl.readline(">> ".to_string(), Some(jobs.len() + 1))? {
+            ParsedQuery::Id(id) => {
+                match self.downloads.file(id - 1) {
+                    Some(path) => {
+                        let vars = [
+                            ("title", jobs[id - 1].title.clone()),
+                            ("start", "0".to_string()),
+                        ];
+                        let title = vars[0].1.clone();
+                        let mut command = self.player_command(&vars, self.config.audio_only());
+                        self.run_command(title, command.arg(path), Error::VideoLaunch)?;
+                    }
+                    None => self.display.warn(&"This download isn't finished yet"),
+                }
+                *changed_action = false;
+                Ok(())
+            }
+            new_action => {
+                *action = new_action;
+                *changed_action = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn download(&mut self, mode: &Mode, ids: &[usize]) {
+        match mode {
+            Mode::Videos(v) => {
+                for id in ids {
+                    self.downloads
+                        .add_video(v.current()[id - 1].clone(), self.config.audio_only());
+                }
+                self.display.info(&format!(
+                    "{} video(s) added to the downloads (:downloads to see the progress)",
+                    ids.len()
+                ));
+                let ids_str: Vec<String> = ids.iter().map(usize::to_string).collect();
+                self.rl
+                    .add_history_entry(&format!(":download {}", ids_str.join(" ")));
+            }
+            Mode::Downloads => self.display.err(&"Downloads are already being downloaded"),
+            _ => self.display.err(&"Only videos can be downloaded"),
+        }
+    }
+
     fn comments(&mut self, mode: &mut Mode, id: usize) {
         match mode {
             Mode::Videos(v) => {
@@ -384,6 +1165,7 @@ impl Cli {
             }
             Mode::Channels(_) => self.display.err(&"Channels don't have comments"),
             Mode::Comments(_) => self.display.err(&"Comments don't have comments"),
+            Mode::Downloads => self.display.err(&"Downloads don't have comments"),
             Mode::Temp => panic!("Bad use of temp"),
         }
     }
@@ -392,7 +1174,9 @@ impl Cli {
         match mode {
             Mode::Videos(v) => self.display.video_info(&v.current()[id - 1]),
             Mode::Channels(c) => self.display.channel_info(&c.current()[id - 1]),
-            Mode::Comments(_) => self.display.warn(&"No additional info available"),
+            Mode::Comments(_) | Mode::Downloads => {
+                self.display.warn(&"No additional info available")
+            }
             Mode::Temp => panic!("Bad use of temp"),
         }
         self.rl.std_in("Press enter to continue".to_string())?;
@@ -400,48 +1184,184 @@ impl Cli {
     }
 
     fn open_browser(&mut self, mode: &Mode, id: usize) -> Result<(), Error> {
-        match &mode {
+        let (title, url) = match &mode {
             Mode::Videos(v) => {
-                self.display.video_info(&v.current()[id - 1]);
-                Command::new(self.config.browser())
-                    .arg(v.current()[id - 1].watch_url())
-                    .spawn()
-                    .map_err(Error::BrowserLaunch)?
-                    .wait()
-                    .map_err(Error::BrowserLaunch)?;
+                let v = &v.current()[id - 1];
+                self.display.video_info(v);
+                (v.name().to_owned(), v.watch_url())
             }
             Mode::Channels(c) => {
                 self.display.channel_info(&c.current()[id - 1]);
                 let c = &c.current()[id - 1];
-                Command::new(self.config.browser())
-                    .arg(self.instance.channel_url(c.host(), c))
-                    .spawn()
-                    .map_err(Error::BrowserLaunch)?
-                    .wait()
-                    .map_err(Error::BrowserLaunch)?;
+                (
+                    c.display_name().to_owned(),
+                    self.instance.channel_url(c.host(), c),
+                )
+            }
+            Mode::Comments(c) => {
+                let c = &c.current()[id - 1];
+                (
+                    format!("Comment from {}", c.author_display_name()),
+                    c.url().to_owned(),
+                )
+            }
+            Mode::Downloads => {
+                self.display
+                    .err(&"Downloads can't be opened in the browser");
+                return Ok(());
+            }
+            Mode::Temp => panic!("Bad use of temp"),
+        };
+
+        self.run_command(
+            format!("Browser: {title}"),
+            Command::new(self.config.browser()).arg(url),
+            Error::BrowserLaunch,
+        )
+    }
+
+    /// Run a command defined in the config on an item of the current list
+    fn custom_command(&mut self, mode: &Mode, name: &str, id: usize) -> Result<(), Error> {
+        let Some(template) = self.config.custom_command(name).map(|c| c.command.clone()) else {
+            return Ok(());
+        };
+        // The values are quoted for sh, cmd.exe would run what follows a `&` in a title
+        if cfg!(not(unix)) {
+            self.display
+                .err(&"Commands are only available on unix systems");
+            return Ok(());
+        }
+
+        let mut vars = match mode {
+            Mode::Videos(v) => {
+                let v = &v.current()[id - 1];
+                let mut vars = vec![
+                    ("title", v.name().to_owned()),
+                    ("handle", v.channel_handle()),
+                    ("host", v.host().to_owned()),
+                    ("uuid", v.uuid().to_owned()),
+                    ("watch_url", v.watch_url()),
+                ];
+                // Fetching the files is only needed for this placeholder
+                if template.contains("{raw_url}") {
+                    match self.pick_file(v, self.config.resolution_policy())? {
+                        Some(url) => vars.push(("raw_url", url)),
+                        None => {
+                            self.display.err(&"No file available for this video");
+                            return Ok(());
+                        }
+                    }
+                }
+                vars
+            }
+            Mode::Channels(c) => {
+                let c = &c.current()[id - 1];
+                vec![
+                    ("title", c.display_name().to_owned()),
+                    ("handle", c.handle()),
+                    ("host", c.host().to_owned()),
+                    ("watch_url", self.instance.channel_url(c.host(), c)),
+                ]
             }
             Mode::Comments(c) => {
-                Command::new(self.config.browser())
-                    .arg(c.current()[id - 1].url())
-                    .spawn()
-                    .map_err(Error::BrowserLaunch)?
-                    .wait()
-                    .map_err(Error::BrowserLaunch)?;
+                let c = &c.current()[id - 1];
+                vec![
+                    ("title", format!("Comment from {}", c.author_display_name())),
+                    ("handle", c.author_handle()),
+                    ("host", c.author_host().to_owned()),
+                    ("watch_url", c.url().to_owned()),
+                ]
+            }
+            Mode::Downloads => {
+                self.display
+                    .err(&format!(":{name} can't be used on downloads"));
+                return Ok(());
             }
             Mode::Temp => panic!("Bad use of temp"),
+        };
+        if template.contains("{raw_url}") && !vars.iter().any(|(n, _)| *n == "raw_url") {
+            self.display.err(&format!(
+                ":{name} uses {{raw_url}}, which is only available for videos"
+            ));
+            return Ok(());
         }
 
-        Ok(())
+        let title = format!("{name}: {}", vars[0].1);
+        for (_, value) in vars.iter_mut() {
+            *value = template::shell_quote(value);
+        }
+        let command = template::expand(&template, &vars);
+
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        self.run_command(title, &mut shell, Error::CommandLaunch)
+    }
+
+    /// Run a command and wait for it, or let it run in the background if players are detached
+    fn run_command(
+        &mut self,
+        title: String,
+        command: &mut Command,
+        err: fn(io::Error) -> Error,
+    ) -> Result<(), Error> {
+        if self.config.detach() {
+            self.players.spawn(title, command).map_err(err)
+        } else {
+            command
+                .spawn()
+                .and_then(|mut child| child.wait())
+                .map_err(err)?;
+            Ok(())
+        }
     }
 
-    fn play_vid(&mut self, video: &peertube_api::Video) -> Result<(), Error> {
+    /// Play a video, `resolution` overrides the preferred resolution of the configuration
+    ///
+    /// Returns the progress of the playback when it could be followed
+    fn play_vid(
+        &mut self,
+        video: &peertube_api::Video,
+        resolution: Option<u64>,
+    ) -> Result<Option<mpv::Progress>, Error> {
+        self.play_vid_with(video, resolution, None)
+    }
+
+    /// Play a video with a player profile, or the one selected by the rules if None
+    fn play_vid_with(
+        &mut self,
+        video: &peertube_api::Video,
+        resolution: Option<u64>,
+        profile: Option<&str>,
+    ) -> Result<Option<mpv::Progress>, Error> {
+        let profile = profile
+            .or_else(|| self.config.profile_for(video.host(), video.is_live()))
+            .map(str::to_owned);
+        self.config.set_profile(profile);
+        let res = match self.video_url(video, resolution) {
+            Ok(Some(url)) => {
+                let audio_only = self.config.audio_only() && resolution.is_none();
+                self.launch(video, url, audio_only)
+            }
+            Ok(None) => Ok(None),
+            Err(err) => Err(err),
+        };
+        self.config.set_profile(None);
+        res
+    }
+
+    /// Url to give to the player, None if the video should be skipped
+    fn video_url(
+        &mut self,
+        video: &peertube_api::Video,
+        resolution: Option<u64>,
+    ) -> Result<Option<String>, Error> {
         // Resolution selection
         self.display.video_info(video);
-        if self.config.is_blocked(video.host()).is_some() {
-            self.display.err(&"This video is from a blocked instance.");
+        if let Some(reason) = self.config.is_blocked(video) {
+            self.display.err(&reason);
             let confirm = self.rl.std_in("Play it anyway ? [y/N]: ".to_string())?;
             if confirm != "y" && confirm != "Y" {
-                return Ok(());
+                return Ok(None);
             }
         }
 
@@ -460,10 +1380,15 @@ impl Cli {
                 _ => unreachable!(),
             }
 
-            return Ok(());
+            return Ok(None);
         }
 
-        let video_url = if self.config.select_quality() {
+        let policy = match resolution {
+            Some(r) => self.config.resolution_policy().with_preferred(r),
+            None => self.config.resolution_policy(),
+        };
+        // A resolution given with the command takes precedence over select-quality and audio-only
+        let video_url = if self.config.select_quality() && resolution.is_none() {
             let resolutions = video.resolutions()?;
             let nb_resolutions = resolutions.len();
 
@@ -475,7 +1400,7 @@ impl Cli {
                 } else if self.config.use_torrent() {
                     self.display
                         .warn(&"Unable to fetch torrent url\nThis video will be skipped");
-                    return Ok(());
+                    return Ok(None);
                 } else {
                     self.display
                         .warn(&"Unable to fetch resolutions\nAttempting to play with watch url");
@@ -492,6 +1417,41 @@ impl Cli {
                     video.resolution_url(choice - 1)?
                 }
             }
+        } else if self.config.audio_only() && resolution.is_none() {
+            video.load_resolutions()?;
+            let url = if self.config.use_torrent() {
+                video.audio_torrent_url()
+            } else {
+                video.audio_url()
+            };
+
+            match url {
+                Ok(url) => url,
+                Err(
+                    peertube_api::error::Error::OutOfBound(_)
+                    | peertube_api::error::Error::NoContent,
+                ) => {
+                    self.display
+                        .warn(&"No audio or video file available\nThis video will be skipped");
+                    return Ok(None);
+                }
+                Err(err) => return Err(err.into()),
+            }
+        } else if policy.is_set() {
+            video.load_resolutions()?;
+            match self.pick_resolution(video, policy)? {
+                Some(url) => url,
+                None if self.config.use_torrent() => {
+                    self.display
+                        .warn(&"Unable to fetch torrent url\nThis video will be skipped");
+                    return Ok(None);
+                }
+                None => {
+                    self.display
+                        .warn(&"Unable to fetch raw video url\nAttempting to play with watch url");
+                    video.watch_url()
+                }
+            }
         } else if self.config.use_torrent() {
             video.load_resolutions()?;
 
@@ -500,7 +1460,7 @@ impl Cli {
                 Err(peertube_api::error::Error::OutOfBound(_)) => {
                     self.display
                         .warn(&"Unable to fetch torrent url\nThis video will be skipped");
-                    return Ok(());
+                    return Ok(None);
                 }
                 Err(err) => return Err(err.into()),
             }
@@ -531,18 +1491,228 @@ impl Cli {
         } else {
             video.watch_url()
         };
-        self.history.add_video(video.uuid().to_string());
-
-        Command::new(self.config.player())
-            .args(self.config.player_args())
-            .arg(video_url)
-            .spawn()
-            .map_err(Error::VideoLaunch)?
-            .wait()
-            .map_err(Error::VideoLaunch)?;
+        Ok(Some(video_url))
+    }
+
+    fn launch(
+        &mut self,
+        video: &peertube_api::Video,
+        video_url: String,
+        audio_only: bool,
+    ) -> Result<Option<mpv::Progress>, Error> {
+        let use_ipc = self.config.use_ipc();
+        let mut start = 0;
+        if let Some(pos) = self.history.position(video.uuid()) {
+            let confirm = self
+                .rl
+                .std_in(format!("Resume from {}? [Y/n]: ", pretty_duration(pos)))?;
+            if confirm != "n" && confirm != "N" {
+                start = pos;
+            }
+        }
+        if !use_ipc {
+            self.history.add_video(video);
+        }
+        self.config.hooks().run_unless_private(
+            self.private,
+            Event::Play,
+            &hooks::video_data(video),
+        );
+
+        let mut command = self.player_command(&template::video_vars(video, start), audio_only);
+        if use_ipc {
+            let socket = mpv::socket_path();
+            command.arg(format!("--input-ipc-server={}", socket.display()));
+            if start > 0 {
+                command.arg(format!("--start={start}"));
+            }
+            let mut child = command.arg(video_url).spawn().map_err(Error::VideoLaunch)?;
+            let progress = mpv::follow(&mut child, &socket).map_err(Error::VideoLaunch)?;
+            self.record_progress(video, progress);
+            Ok(Some(progress))
+        } else {
+            self.run_command(
+                video.name().to_owned(),
+                command.arg(video_url),
+                Error::VideoLaunch,
+            )?;
+            Ok(None)
+        }
+    }
+
+    /// Player command with its arguments, without the url to play
+    /// `audio_only` adds the audio-only arguments of the player
+    fn player_command(&self, vars: &[(&str, String)], audio_only: bool) -> Command {
+        let mut command = Command::new(self.config.player());
+        command.args(
+            self.config
+                .player_args()
+                .iter()
+                .map(|arg| template::expand(arg, vars)),
+        );
+        if audio_only {
+            command.args(self.config.audio_only_args());
+        }
+        command
+    }
+
+    /// Whether to continue with the next video of the queue or of the autoplay
+    ///
+    /// When the playback was followed, it continues only if the previous video was watched until
+    /// the end, otherwise the user is asked
+    fn continue_with(
+        &mut self,
+        progress: Option<mpv::Progress>,
+        next: &peertube_api::Video,
+    ) -> Result<bool, Error> {
+        match progress {
+            Some(p) => Ok(p.eof),
+            None => {
+                let confirm = self
+                    .rl
+                    .std_in(format!("Play next video: {}? [Y/n]: ", next.name()))?;
+                Ok(confirm != "n" && confirm != "N")
+            }
+        }
+    }
+
+    fn show_stats(&self) {
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        let stats = stats::compute(&self.history.entries(""), self.history.abandoned(), offset);
+        self.display.stats(&stats);
+    }
+
+    fn show_queue(&mut self) -> Result<(), Error> {
+        self.display
+            .video_list(&self.queue, &self.history, &self.config);
+        self.display.info(&format!(
+            "{} video(s) in the queue (:play-queue to play them)",
+            self.queue.len()
+        ));
+        self.rl.std_in("Press enter to continue".to_string())?;
         Ok(())
     }
 
+    fn play_queue(&mut self) -> Result<(), Error> {
+        if self.queue.is_empty() {
+            self.display
+                .warn(&"The queue is empty (:queue <ids> to add videos)");
+            return Ok(());
+        }
+
+        if !self.config.use_playlists() {
+            while !self.queue.is_empty() {
+                let video = self.queue.remove(0);
+                let progress = self.play_vid(&video, None)?;
+                match self.queue.first().cloned() {
+                    Some(next) if self.continue_with(progress, &next)? => {}
+                    _ => break,
+                }
+            }
+            return Ok(());
+        }
+
+        let queue = std::mem::take(&mut self.queue);
+        let mut videos = Vec::new();
+        for video in queue {
+            if let Some(url) = self.video_url(&video, None)? {
+                videos.push((video, url));
+            }
+        }
+        let Some((first, _)) = videos.first() else {
+            return Ok(());
+        };
+        let entries: Vec<_> = videos
+            .iter()
+            .map(|(v, url)| queue::Entry {
+                title: v.name(),
+                duration: v.duration(),
+                url,
+            })
+            .collect();
+        let playlist = queue::playlist_path();
+        std::fs::write(&playlist, queue::m3u(&entries)).map_err(Error::VideoLaunch)?;
+
+        // The player doesn't report which videos of the playlist are played
+        self.history.add_video(first);
+        self.config.hooks().run_unless_private(
+            self.private,
+            Event::Play,
+            &hooks::video_data(first),
+        );
+        let title = format!("Queue of {} video(s)", videos.len());
+        let mut command = self.player_command(&template::playlist_vars(), self.config.audio_only());
+        let res = self.run_command(title, command.arg(&playlist), Error::VideoLaunch);
+        // A detached player still needs the playlist
+        if !self.config.detach() {
+            std::fs::remove_file(&playlist).unwrap_or(());
+        }
+        res
+    }
+
+    /// Mark the video as seen if enough of it was watched, otherwise remember the position
+    fn record_progress(&mut self, video: &peertube_api::Video, progress: mpv::Progress) {
+        let uuid = video.uuid().to_string();
+        if progress.watched(video.duration()) >= self.config.watched_threshold() {
+            self.history.remove_position(&uuid);
+            self.history.add_video(video);
+        } else if let Some(pos) = progress.position.filter(|p| *p >= 1.) {
+            self.history.set_position(uuid, pos as u64);
+        }
+    }
+
+    /// Url of the file, HLS variant or torrent chosen by the resolution policy
+    fn pick_resolution(
+        &self,
+        video: &peertube_api::Video,
+        policy: ResolutionPolicy,
+    ) -> Result<Option<String>, Error> {
+        let ids = |resolutions: Vec<peertube_api::Resolution>| {
+            resolutions.iter().map(|r| *r.id()).collect::<Vec<_>>()
+        };
+        if self.config.use_torrent() {
+            return match policy.pick(&ids(video.resolutions()?)) {
+                Some(i) => Ok(Some(video.torrent_url(i)?)),
+                None => Ok(None),
+            };
+        }
+
+        self.pick_file(video, policy)
+    }
+
+    /// Url of the file or HLS stream matching the policy, ignoring the torrent configuration
+    fn pick_file(
+        &self,
+        video: &peertube_api::Video,
+        policy: ResolutionPolicy,
+    ) -> Result<Option<String>, Error> {
+        let ids = |resolutions: Vec<peertube_api::Resolution>| {
+            resolutions.iter().map(|r| *r.id()).collect::<Vec<_>>()
+        };
+        if (self.config.prefer_hls() && video.has_streams()?) || !video.has_files()? {
+            match policy.pick(&video.stream_resolutions()?) {
+                Some(i) => Ok(Some(video.stream_variant_url(i)?)),
+                None if video.has_streams()? => Ok(Some(video.stream_url(0)?)),
+                None => Ok(None),
+            }
+        } else {
+            match policy.pick(&ids(video.resolutions()?)) {
+                Some(i) => Ok(Some(video.resolution_url(i)?)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    fn error_hook(&self, message: Option<String>) {
+        if let Some(message) = message {
+            self.config.hooks().run_unless_private(
+                self.private,
+                Event::Error,
+                &[("error", message)],
+            );
+        }
+    }
+
     /// Returns None if the error was dealt with
     fn handle_err(&mut self, err: Error) -> Option<Error> {
         match &err {
@@ -614,7 +1784,42 @@ impl Cli {
     }
 
     pub fn run(&mut self) {
-        self.main_loop().unwrap_or_else(|e| self.top_level_err(e));
+        let result = if self.config.tui() {
+            self.tui()
+        } else {
+            self.main_loop()
+        };
+        result.unwrap_or_else(|e| {
+            self.error_hook(error_message(&e));
+            self.top_level_err(e)
+        });
+        // Wait for the hook so that it can read its input before exiting
+        let hook = self
+            .config
+            .hooks()
+            .run_unless_private(self.private, Event::Quit, &[]);
+        if let Some(hook) = hook {
+            hook.join().unwrap_or(());
+        }
+    }
+}
+
+/// Host and uuid of the saved videos, to fetch them again
+fn saved_ids(videos: &[SavedVideo]) -> Vec<(String, String)> {
+    videos
+        .iter()
+        .map(|v| (v.host.clone(), v.uuid.clone()))
+        .collect()
+}
+
+/// Message given to the error hook, None when the user exited
+fn error_message(err: &Error) -> Option<String> {
+    match err {
+        Error::Readline(ReadlineError::Interrupted | ReadlineError::Eof) => None,
+        err => match err.source() {
+            Some(source) => Some(format!("{err}: {source}")),
+            None => Some(err.to_string()),
+        },
     }
 }
 
@@ -629,6 +1834,7 @@ enum Mode {
     Videos(PreloadableList<Videos>),
     Channels(PreloadableList<Channels>),
     Comments(PreloadableList<Comments>),
+    Downloads,
     Temp,
 }
 
@@ -638,6 +1844,7 @@ impl Mode {
             Mode::Videos(v) => Ok(v.ensure_init()?),
             Mode::Channels(c) => Ok(c.ensure_init()?),
             Mode::Comments(c) => Ok(c.ensure_init()?),
+            Mode::Downloads => Ok(()),
             Mode::Temp => panic!("Bad use of temp"),
         }
     }
@@ -650,14 +1857,34 @@ impl Mode {
 impl Drop for Cli {
     fn drop(&mut self) {
         if let Some(d) = self.dirs.as_ref() {
-            let mut view_hist_file = d.cache_dir().to_owned();
-            view_hist_file.push("history");
-            self.history
-                .save(&view_hist_file, self.config.max_hist_lines())
-                .unwrap_or(());
-            let mut cmd_hist_file = d.cache_dir().to_owned();
-            cmd_hist_file.push("cmd_history");
-            self.rl.save_history(&cmd_hist_file).unwrap_or(());
+            // The lists and downloads are user data, only the history is not saved
+            if !self.private {
+                let mut view_hist_file = d.cache_dir().to_owned();
+                view_hist_file.push("history.toml");
+                if self
+                    .history
+                    .save(&view_hist_file, self.config.max_hist_lines())
+                    .is_ok()
+                {
+                    view_hist_file.set_file_name("history");
+                    remove_file(&view_hist_file).unwrap_or(());
+                }
+                let mut positions_file = d.cache_dir().to_owned();
+                positions_file.push("positions");
+                self.history.save_positions(&positions_file).unwrap_or(());
+                let mut cmd_hist_file = d.cache_dir().to_owned();
+                cmd_hist_file.push("cmd_history");
+                self.rl.save_history(&cmd_hist_file).unwrap_or(());
+            }
+
+            let data = d.data_dir();
+            create_dir_all(data).unwrap_or(());
+            let mut downloads_file = data.to_owned();
+            downloads_file.push("downloads.toml");
+            self.downloads.save(&downloads_file).unwrap_or(());
+            let mut lists_file = data.to_owned();
+            lists_file.push("lists.toml");
+            self.lists.save(&lists_file).unwrap_or(());
         }
     }
 }
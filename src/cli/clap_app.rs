@@ -33,6 +33,14 @@ pub fn gen_app() -> Command {
                 .long("select-quality")
                 .action(ArgAction::SetTrue)
                 .help("When playing a video with this option, the user will be prompted to chose the video quality\n Note: this implies --use-raw-urls"),
+            Arg::new("audio-only")
+                .long("audio-only")
+                .action(ArgAction::SetTrue)
+                .help("Play and download only the audio of the videos when an audio-only version is available, the lowest resolution is used otherwise"),
+            Arg::new("private")
+                .long("private")
+                .action(ArgAction::SetTrue)
+                .help("Don't save the viewing history or the command history of this session, don't run the hooks, and use a generic user agent"),
             Arg::new("local")
                 .long("local")
                 .action(ArgAction::SetTrue)
@@ -48,6 +56,14 @@ pub fn gen_app() -> Command {
                 .conflicts_with("chandle")
                 .action(ArgAction::SetTrue)
                 .help("Will start browsing trending videos"),
+            Arg::new("tui")
+                .long("tui")
+                .action(ArgAction::SetTrue)
+                .help("Browse in a full-screen interface, moving between the results with the arrow keys"),
+            Arg::new("stats")
+                .long("stats")
+                .action(ArgAction::SetTrue)
+                .help("Print statistics on the videos watched and exit"),
             Arg::new("channels")
                 .long("channels")
                 .conflicts_with("trending")
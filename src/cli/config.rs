@@ -6,33 +6,53 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
+mod blocklist_files;
+mod edit;
 mod frontend_url_parser;
 
 use clap::ArgMatches;
 use directories::ProjectDirs;
+use regex::Regex;
 use rustyline::config::EditMode;
 use toml::{
     de::Error as TomlError,
     value::{Table, Value},
 };
 
+use blocklist_files::{BlocklistFiles, Entries};
 use frontend_url_parser::{ParsedUrl, UrlType};
+use peertube_api::{channels::Channel, Comment, Video};
 use peertube_viewer_utils::to_https;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
 use std::env::{var, vars_os};
 use std::ffi::OsString;
 use std::fmt::{self, Display};
-use std::fs::read_to_string;
+use std::fs::{create_dir_all, read_to_string, write};
 use std::path::{Path, PathBuf};
-use std::process::exit;
+use std::process::{exit, Command};
 use std::{error, io};
 
 use super::clap_app::gen_app;
+use super::display::layout::{
+    parse_layout, ChannelLayoutItem, CommentLayoutItem, InnerLayoutItem, LayoutError,
+    VideoLayoutItem,
+};
+use super::display::theme::{Style, Theme, PRESETS, ROLES};
+use super::hooks::Hooks;
+use super::mpv::{is_mpv, IPC_SUPPORTED};
+use super::parser::is_builtin;
+use super::queue::supports_playlists;
+use super::resolution::{Prefer, ResolutionPolicy};
 
 pub trait Blocklist<T: ?Sized> {
     fn is_blocked(&self, instance: &T) -> Option<String>;
+
+    /// Whether blocked items are hidden instead of replaced by the reason they are blocked
+    fn hide_blocked(&self) -> bool {
+        false
+    }
 }
 
 impl<T: ?Sized> Blocklist<T> for () {
@@ -41,24 +61,208 @@ impl<T: ?Sized> Blocklist<T> for () {
     }
 }
 
+/// What can be blocked from the list of videos, channels or comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Instance,
+    Channel,
+    Account,
+}
+
+impl BlockKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            BlockKind::Instance => "instance",
+            BlockKind::Channel => "channel",
+            BlockKind::Account => "account",
+        }
+    }
+}
+
+/// Channels, accounts and keywords whose content is blocked
+#[derive(Debug, Default)]
+struct ContentBlocklist {
+    /// Handles of the channels (ex: `name@host`)
+    channels: HashSet<String>,
+    accounts: HashSet<String>,
+    /// Matched against titles, descriptions and comments
+    keywords: Vec<Regex>,
+    hide: bool,
+}
+
+impl PartialEq for ContentBlocklist {
+    fn eq(&self, other: &Self) -> bool {
+        self.channels == other.channels
+            && self.accounts == other.accounts
+            && self.hide == other.hide
+            && self
+                .keywords
+                .iter()
+                .map(Regex::as_str)
+                .eq(other.keywords.iter().map(Regex::as_str))
+    }
+}
+
+impl ContentBlocklist {
+    /// Reason why the content is blocked if one of the texts matches a keyword
+    fn keyword<'a>(&self, texts: impl IntoIterator<Item = &'a str> + Clone) -> Option<String> {
+        self.keywords
+            .iter()
+            .find(|k| texts.clone().into_iter().any(|t| k.is_match(t)))
+            .map(|k| format!("Blocked keyword: {}", k.as_str()))
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct TorrentConf {
     pub client: String,
     pub args: Vec<String>,
 }
 
+#[derive(Debug, PartialEq)]
+struct DownloadConf {
+    pub directory: Option<PathBuf>,
+    pub parallel: usize,
+    pub sidecars: SidecarConf,
+}
+
+/// Format of the metadata file written next to downloaded videos
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MetadataFormat {
+    None,
+    Json,
+    Nfo,
+}
+
+/// Account of the main instance, used for the mute lists
+#[derive(Debug, PartialEq, Eq)]
+pub struct AccountConf {
+    pub username: String,
+    password: Option<String>,
+    /// Command printing the password, to avoid writing it in the config
+    password_command: Vec<String>,
+}
+
+impl AccountConf {
+    pub fn password(&self) -> Result<String, io::Error> {
+        if let Some(password) = &self.password {
+            return Ok(password.clone());
+        }
+        let (program, args) = self.password_command.split_first().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "neither password nor password-command are set",
+            )
+        })?;
+        let output = Command::new(program).args(args).output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "password-command exited with {}: {}",
+                    output.status,
+                    stderr.trim()
+                ),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_owned())
+    }
+}
+
+/// Templates of the lists, the default ones are used when not set
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct LayoutConf {
+    pub video: Option<String>,
+    pub seen_video: Option<String>,
+    pub channel: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// Files written next to downloaded videos
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SidecarConf {
+    pub metadata: MetadataFormat,
+    pub thumbnail: bool,
+    pub captions: bool,
+}
+
+impl SidecarConf {
+    pub fn is_empty(&self) -> bool {
+        self.metadata == MetadataFormat::None && !self.thumbnail && !self.captions
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct PlayerConf {
     pub client: String,
     pub args: Vec<String>,
     pub use_raw_urls: bool,
     pub prefer_hls: bool,
+    pub audio_only: bool,
+    pub audio_only_args: Vec<String>,
+    pub resolution: ResolutionPolicy,
+    pub ipc: Support,
+    pub watched_threshold: f64,
+    pub playlists: Support,
+    pub detach: bool,
+}
+
+/// Alternative player defined in a `[players.<name>]` table
+#[derive(Debug, PartialEq)]
+struct PlayerProfile {
+    client: String,
+    args: Vec<String>,
+    audio_only_args: Vec<String>,
+    use_raw_urls: bool,
+    prefer_hls: bool,
+}
+
+/// Rule selecting a player profile, every condition that is set needs to match
+#[derive(Debug, PartialEq)]
+struct PlayerRule {
+    host: Option<String>,
+    live: Option<bool>,
+    profile: String,
+}
+
+impl PlayerRule {
+    fn matches(&self, host: &str, live: bool) -> bool {
+        self.host.as_ref().map_or(true, |h| h == host) && self.live.map_or(true, |l| l == live)
+    }
+}
+
+/// Command defined in a `[commands.<name>]` table, ran with `:<name> <id>`
+#[derive(Debug, PartialEq)]
+pub struct CustomCommand {
+    pub name: String,
+    /// Shell template where the placeholders are replaced by quoted values
+    pub command: String,
+    pub description: Option<String>,
+}
+
+/// Whether a feature depending on the player is used
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Support {
+    /// Only for the players known to support it
+    Auto,
+    Enable,
+    Disable,
 }
 
 const NSFW_ALLOWED: [&str; 3] = ["tag", "block", "let"];
 const COLORS_ALLOWED: [&str; 2] = ["enable", "disable"];
 const EDIT_MODE_ALLOWED: [&str; 2] = ["emacs", "vi"];
+const METADATA_ALLOWED: [&str; 3] = ["none", "json", "nfo"];
+const PREFER_ALLOWED: [&str; 2] = ["highest-below", "closest"];
+const SUPPORT_ALLOWED: [&str; 3] = ["auto", "enable", "disable"];
 const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+/// Generic user agent used in private mode
+pub const PRIVATE_USER_AGENT: &str = "Mozilla/5.0";
 
 #[derive(Debug)]
 pub enum ConfigLoadError {
@@ -67,6 +271,15 @@ pub enum ConfigLoadError {
     UseTorrentAndNoInfo,
     NotATable,
     NotAString(String),
+    NotAPositiveInteger(String),
+    NotAShare(String),
+    BadPlayerRule(String),
+    UnknownProfile(String),
+    InvalidCommandName(String),
+    InvalidRegex(regex::Error),
+    UnreadableBlocklist(io::Error, PathBuf),
+    InvalidLayout(&'static str, LayoutError),
+    InvalidStyle(String, String),
     NonUtf8EnvironmentVariable {
         name: &'static str,
         provided: OsString,
@@ -92,9 +305,9 @@ impl Display for ConfigLoadError {
                 f,
                 "The config was not parsable as TOML:\n{e}\nUsing default config"
             ),
-            ConfigLoadError::NonUtf8EnvironmentVariable{name,provided:_} => write!(
+            ConfigLoadError::NonUtf8EnvironmentVariable{name,provided} => write!(
                 f,
-                "Environnment variable {name} is not utf8." ,
+                "Environnment variable {name} is not utf8: {provided:?}" ,
             ),
             ConfigLoadError::IncorrectTag{name,provided,allowed} => write!(
                 f,
@@ -112,6 +325,43 @@ impl Display for ConfigLoadError {
                 f,
                 "{s} needs to be Strings\n Ignoring bad arguments"
             ),
+            ConfigLoadError::NotAPositiveInteger(s) => write!(
+                f,
+                "{s} needs to be a positive integer\nUsing default value"
+            ),
+            ConfigLoadError::NotAShare(s) => write!(
+                f,
+                "{s} needs to be a number between 0 and 1\nUsing default value"
+            ),
+            ConfigLoadError::BadPlayerRule(s) => write!(
+                f,
+                "Invalid player rule: {s}\nIgnoring it"
+            ),
+            ConfigLoadError::UnknownProfile(s) => write!(
+                f,
+                "A player rule uses the profile \"{s}\" which isn't defined\nIgnoring it"
+            ),
+            ConfigLoadError::InvalidCommandName(s) => write!(
+                f,
+                "\"{s}\" can't be used as a command name, it is either a command of peertube-viewer-rs or contains spaces\nIgnoring it"
+            ),
+            ConfigLoadError::InvalidRegex(e) => write!(
+                f,
+                "Invalid keyword in the blocklist:\n{e}\nIgnoring it"
+            ),
+            ConfigLoadError::UnreadableBlocklist(e, path) => write!(
+                f,
+                "Unable to read the blocklist file {}:\n{e}\nIgnoring it",
+                path.display()
+            ),
+            ConfigLoadError::InvalidLayout(name, e) => write!(
+                f,
+                "Invalid layout for {name}: {e}\nUsing the default one"
+            ),
+            ConfigLoadError::InvalidStyle(role, style) => write!(
+                f,
+                "Invalid style for {role}: \"{style}\"\nUsing the one of the preset"
+            ),
             ConfigLoadError::UseTorrentAndNoInfo=> write!(
                 f,
                 "--use-torrent requires a torrent to be set\nUsing player instead of torrent"
@@ -129,6 +379,8 @@ impl error::Error for ConfigLoadError {
         match self {
             ConfigLoadError::UnreadableFile(e, _) => Some(e),
             ConfigLoadError::TomlError(e) => Some(e),
+            ConfigLoadError::InvalidRegex(e) => Some(e),
+            ConfigLoadError::UnreadableBlocklist(e, _) => Some(e),
             ConfigLoadError::IncorrectTag {
                 name: _,
                 provided: _,
@@ -141,7 +393,14 @@ impl error::Error for ConfigLoadError {
             | ConfigLoadError::ConflicingOptions(_, _)
             | ConfigLoadError::UseTorrentAndNoInfo
             | ConfigLoadError::NotATable
-            | ConfigLoadError::NotAString(_) => None,
+            | ConfigLoadError::NotAString(_)
+            | ConfigLoadError::NotAPositiveInteger(_)
+            | ConfigLoadError::NotAShare(_)
+            | ConfigLoadError::BadPlayerRule(_)
+            | ConfigLoadError::UnknownProfile(_)
+            | ConfigLoadError::InvalidCommandName(_)
+            | ConfigLoadError::InvalidLayout(_, _)
+            | ConfigLoadError::InvalidStyle(_, _) => None,
         }
     }
 }
@@ -176,6 +435,7 @@ pub enum InitialInfo {
     Channels(String),
     Handle(String),
     Trending,
+    Stats,
 }
 
 impl InitialInfo {
@@ -190,11 +450,26 @@ impl InitialInfo {
 #[derive(Debug, PartialEq)]
 pub struct Config {
     player: PlayerConf,
+    profiles: HashMap<String, PlayerProfile>,
+    player_rules: Vec<PlayerRule>,
+    /// Profile used for the video being played
+    active_profile: Option<String>,
+    commands: Vec<CustomCommand>,
+    hooks: Hooks,
     instance: String,
     is_search_engine: bool,
     torrent: Option<(TorrentConf, bool)>,
+    download: DownloadConf,
     listed_instances: HashSet<String>,
     is_allowlist: bool,
+    blocklist: ContentBlocklist,
+    blocklist_files: BlocklistFiles,
+    account: Option<AccountConf>,
+    /// Mirror of the mute lists of the account
+    muted_instances: HashSet<String>,
+    muted_accounts: HashSet<String>,
+    layouts: LayoutConf,
+    theme: Theme,
 
     user_agent: Option<String>,
 
@@ -205,8 +480,14 @@ pub struct Config {
     select_quality: bool,
     colors: bool,
     local: bool,
+    private: bool,
+    /// Browse in the full-screen interface
+    tui: bool,
 
     max_hist_lines: usize,
+
+    /// File the config was loaded from, where the blocklists are saved
+    path: Option<PathBuf>,
 }
 
 impl Config {
@@ -228,21 +509,27 @@ impl Config {
         }
 
         // Parse config as an String with default to empty string
-        let (mut config, mut load_errors) =
-            if let Some(c) = cli_args.get_one::<String>("config-file") {
-                Config::from_config_file(&PathBuf::from(c), false)
-            } else {
-                match ProjectDirs::from("", "peertube-viewer-rs", "peertube-viewer-rs") {
-                    Some(dirs) => {
-                        let mut d = dirs.config_dir().to_owned();
-                        d.push("config.toml");
-                        Config::from_config_file(&d, true)
-                    }
-                    None => (Config::default(), Vec::new()),
-                }
-            };
+        let path = if let Some(c) = cli_args.get_one::<String>("config-file") {
+            Some((PathBuf::from(c), false))
+        } else {
+            ProjectDirs::from("", "peertube-viewer-rs", "peertube-viewer-rs").map(|dirs| {
+                let mut d = dirs.config_dir().to_owned();
+                d.push("config.toml");
+                (d, true)
+            })
+        };
+        let (mut config, mut load_errors) = match path {
+            Some((path, is_default)) => {
+                let (mut config, load_errors) = Config::from_config_file(&path, is_default);
+                config.path = Some(path);
+                (config, load_errors)
+            }
+            None => (Config::default(), Vec::new()),
+        };
 
-        let initial_info = if cli_args.get_flag("trending") {
+        let initial_info = if cli_args.get_flag("stats") {
+            InitialInfo::Stats
+        } else if cli_args.get_flag("trending") {
             InitialInfo::Trending
         } else if let Some(s) = cli_args.get_one::<String>("chandle") {
             InitialInfo::Handle(s.to_string())
@@ -323,7 +610,7 @@ impl Config {
         };
 
         /* ---Player configuration --- */
-        let (player_cmd, player_args, use_raw_urls, prefer_hls) =
+        let (player_cmd, player_args, use_raw_urls, prefer_hls, audio_only, audio_only_args) =
             if let Some(Value::Table(t)) = config.get("player") {
                 (
                     t.get("command")
@@ -337,17 +624,204 @@ impl Config {
                     t.get("prefer-hls")
                         .and_then(|b| b.as_bool())
                         .unwrap_or(true),
+                    t.get("audio-only")
+                        .and_then(|b| b.as_bool())
+                        .unwrap_or(false),
+                    if t.contains_key("audio-only-args") {
+                        get_string_array(t, "audio-only-args", &mut load_errors)
+                    } else {
+                        default_audio_only_args()
+                    },
                 )
             } else {
-                ("mpv".to_string(), Vec::new(), false, true)
+                (
+                    "mpv".to_string(),
+                    Vec::new(),
+                    false,
+                    true,
+                    false,
+                    default_audio_only_args(),
+                )
             };
         temp.player = PlayerConf {
             client: player_cmd,
             args: player_args,
             use_raw_urls,
             prefer_hls,
+            audio_only,
+            audio_only_args,
+            resolution: ResolutionPolicy::default(),
+            ipc: Support::Auto,
+            watched_threshold: 0.9,
+            playlists: Support::Auto,
+            detach: false,
         };
 
+        if let Some(Value::Table(t)) = config.get("player") {
+            for (name, value) in [
+                (
+                    "preferred-resolution",
+                    &mut temp.player.resolution.preferred,
+                ),
+                ("max-resolution", &mut temp.player.resolution.max),
+            ] {
+                match t.get(name) {
+                    Some(Value::Integer(i)) if *i > 0 => *value = Some(*i as u64),
+                    Some(_) => load_errors.push(ConfigLoadError::NotAPositiveInteger(format!(
+                        "player: {name}"
+                    ))),
+                    None => {}
+                }
+            }
+
+            if let Some(Value::String(s)) = t.get("prefer") {
+                if s == "highest-below" {
+                    temp.player.resolution.prefer = Prefer::HighestBelow;
+                } else if s == "closest" {
+                    temp.player.resolution.prefer = Prefer::Closest;
+                } else {
+                    load_errors.push(ConfigLoadError::IncorrectTag {
+                        name: "prefer",
+                        provided: s.to_string(),
+                        allowed: &PREFER_ALLOWED,
+                    });
+                }
+            }
+
+            for (name, value) in [
+                ("ipc", &mut temp.player.ipc),
+                ("playlists", &mut temp.player.playlists),
+            ] {
+                if let Some(Value::String(s)) = t.get(name) {
+                    if s == "auto" {
+                        *value = Support::Auto;
+                    } else if s == "enable" {
+                        *value = Support::Enable;
+                    } else if s == "disable" {
+                        *value = Support::Disable;
+                    } else {
+                        load_errors.push(ConfigLoadError::IncorrectTag {
+                            name,
+                            provided: s.to_string(),
+                            allowed: &SUPPORT_ALLOWED,
+                        });
+                    }
+                }
+            }
+
+            if let Some(Value::Boolean(b)) = t.get("detach") {
+                temp.player.detach = *b;
+            }
+
+            match t.get("watched-threshold") {
+                Some(Value::Float(f)) if (0. ..=1.).contains(f) => {
+                    temp.player.watched_threshold = *f
+                }
+                Some(Value::Integer(i)) if (0..=1).contains(i) => {
+                    temp.player.watched_threshold = *i as f64
+                }
+                Some(_) => load_errors.push(ConfigLoadError::NotAShare(
+                    "player: watched-threshold".to_owned(),
+                )),
+                None => {}
+            }
+        }
+
+        /* ---Player profiles --- */
+        if let Some(Value::Table(t)) = config.get("players") {
+            for (name, value) in t {
+                match value {
+                    Value::Table(p) => {
+                        let Some(command) = p.get("command").and_then(Value::as_str) else {
+                            load_errors.push(ConfigLoadError::NotAString(format!(
+                                "players.{name}: command"
+                            )));
+                            continue;
+                        };
+                        let profile = PlayerProfile {
+                            client: command.to_owned(),
+                            args: get_string_array(p, "args", &mut load_errors),
+                            // The arguments of [player] only make sense for the same player
+                            audio_only_args: if p.contains_key("audio-only-args") {
+                                get_string_array(p, "audio-only-args", &mut load_errors)
+                            } else if command == temp.player.client {
+                                temp.player.audio_only_args.clone()
+                            } else {
+                                Vec::new()
+                            },
+                            use_raw_urls: p
+                                .get("use-raw-urls")
+                                .and_then(Value::as_bool)
+                                .unwrap_or(temp.player.use_raw_urls),
+                            prefer_hls: p
+                                .get("prefer-hls")
+                                .and_then(Value::as_bool)
+                                .unwrap_or(temp.player.prefer_hls),
+                        };
+                        temp.profiles.insert(name.clone(), profile);
+                    }
+                    Value::Array(rules) if name == "rules" => {
+                        for rule in rules {
+                            match parse_player_rule(rule) {
+                                Ok(rule) => temp.player_rules.push(rule),
+                                Err(err) => load_errors.push(err),
+                            }
+                        }
+                    }
+                    _ => load_errors.push(ConfigLoadError::BadPlayerRule(format!(
+                        "players.{name} should be a table"
+                    ))),
+                }
+            }
+
+            let profiles = &temp.profiles;
+            temp.player_rules.retain(|r| {
+                let known = profiles.contains_key(&r.profile);
+                if !known {
+                    load_errors.push(ConfigLoadError::UnknownProfile(r.profile.clone()));
+                }
+                known
+            });
+        }
+
+        /* ---User-defined commands --- */
+        if let Some(Value::Table(t)) = config.get("commands") {
+            for (name, value) in t {
+                if name.is_empty()
+                    || name.contains(char::is_whitespace)
+                    || is_builtin(&format!(":{name}"))
+                {
+                    load_errors.push(ConfigLoadError::InvalidCommandName(name.clone()));
+                    continue;
+                }
+                let Some(command) = value.get("command").and_then(Value::as_str) else {
+                    load_errors.push(ConfigLoadError::NotAString(format!(
+                        "commands.{name}: command"
+                    )));
+                    continue;
+                };
+                temp.commands.push(CustomCommand {
+                    name: name.clone(),
+                    command: command.to_owned(),
+                    description: value
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .map(str::to_owned),
+                });
+            }
+            temp.commands.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        /* ---Hooks --- */
+        if let Some(Value::Table(t)) = config.get("hooks") {
+            temp.hooks = Hooks {
+                on_play: get_hook(t, "on-play", &mut load_errors),
+                on_download_finished: get_hook(t, "on-download-finished", &mut load_errors),
+                on_error: get_hook(t, "on-error", &mut load_errors),
+                on_quit: get_hook(t, "on-quit", &mut load_errors),
+            };
+        }
+
         /* ---Torrent configuration --- */
         let torrent = if let Some(Value::Table(t)) = config.get("torrent") {
             t.get("command")
@@ -360,6 +834,45 @@ impl Config {
             None
         };
 
+        /* ---Download configuration --- */
+        if let Some(Value::Table(t)) = config.get("download") {
+            if let Some(Value::String(s)) = t.get("directory") {
+                temp.download.directory = Some(PathBuf::from(s));
+            }
+
+            match t.get("parallel") {
+                Some(Value::Integer(i)) if *i > 0 => temp.download.parallel = *i as usize,
+                Some(_) => load_errors.push(ConfigLoadError::NotAPositiveInteger(
+                    "download: parallel".to_owned(),
+                )),
+                None => {}
+            }
+
+            if let Some(Value::String(s)) = t.get("metadata") {
+                if s == "none" {
+                    temp.download.sidecars.metadata = MetadataFormat::None;
+                } else if s == "json" {
+                    temp.download.sidecars.metadata = MetadataFormat::Json;
+                } else if s == "nfo" {
+                    temp.download.sidecars.metadata = MetadataFormat::Nfo;
+                } else {
+                    load_errors.push(ConfigLoadError::IncorrectTag {
+                        name: "metadata",
+                        provided: s.to_string(),
+                        allowed: &METADATA_ALLOWED,
+                    });
+                }
+            }
+
+            if let Some(Value::Boolean(b)) = t.get("thumbnail") {
+                temp.download.sidecars.thumbnail = *b;
+            }
+
+            if let Some(Value::Boolean(b)) = t.get("captions") {
+                temp.download.sidecars.captions = *b;
+            }
+        }
+
         /* ---General configuration --- */
         if let Some(Value::Table(t)) = config.get("general") {
             if let Some(Value::String(s)) = t.get("nsfw") {
@@ -488,6 +1001,113 @@ impl Config {
         temp.listed_instances = list;
         temp.is_allowlist = is_allowlist;
 
+        if let Some(Value::Table(t)) = config.get("blocklist") {
+            temp.blocklist.channels = get_string_array(t, "channels", &mut load_errors)
+                .into_iter()
+                .collect();
+            temp.blocklist.accounts = get_string_array(t, "accounts", &mut load_errors)
+                .into_iter()
+                .collect();
+            for keyword in get_string_array(t, "keywords", &mut load_errors) {
+                match Regex::new(&keyword) {
+                    Ok(regex) => temp.blocklist.keywords.push(regex),
+                    Err(e) => load_errors.push(ConfigLoadError::InvalidRegex(e)),
+                }
+            }
+            if let Some(Value::Boolean(hide)) = t.get("hide") {
+                temp.blocklist.hide = *hide;
+            }
+        }
+
+        // Relative paths are relative to the directory of the config file
+        let config_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let files = &mut temp.blocklist_files;
+        for (table, key, entries) in [
+            ("instances", "blocklist-files", &mut files.instances),
+            ("blocklist", "channel-files", &mut files.channels),
+            ("blocklist", "account-files", &mut files.accounts),
+        ] {
+            if let Some(Value::Table(t)) = config.get(table) {
+                for file in get_string_array(t, key, &mut load_errors) {
+                    let file = config_dir.join(file);
+                    if let Err(e) = blocklist_files::load(&file, entries, &mut files.loaded) {
+                        load_errors.push(ConfigLoadError::UnreadableBlocklist(e, file));
+                    }
+                }
+            }
+        }
+
+        if let Some(Value::Table(t)) = config.get("theme") {
+            match t.get("preset") {
+                Some(Value::String(s)) => match Theme::preset(s) {
+                    Some(theme) => temp.theme = theme,
+                    None => load_errors.push(ConfigLoadError::IncorrectTag {
+                        name: "theme: preset",
+                        provided: s.to_string(),
+                        allowed: &PRESETS,
+                    }),
+                },
+                Some(_) => load_errors.push(ConfigLoadError::NotAString("theme: preset".into())),
+                None => {}
+            }
+
+            for (role, value) in t.iter().filter(|(k, _)| *k != "preset") {
+                let spec = match value {
+                    Value::String(s) => s.clone(),
+                    // 256-color values can be written as numbers
+                    Value::Integer(i) => i.to_string(),
+                    _ => {
+                        load_errors.push(ConfigLoadError::NotAString(format!("theme: {role}")));
+                        continue;
+                    }
+                };
+                match (temp.theme.role_mut(role), Style::parse(&spec)) {
+                    (Some(current), Some(style)) => *current = style,
+                    (Some(_), None) => {
+                        load_errors.push(ConfigLoadError::InvalidStyle(role.clone(), spec))
+                    }
+                    (None, _) => load_errors.push(ConfigLoadError::IncorrectTag {
+                        name: "theme",
+                        provided: role.clone(),
+                        allowed: &ROLES,
+                    }),
+                }
+            }
+        }
+
+        if let Some(Value::Table(t)) = config.get("layout") {
+            let (layouts, theme) = (&mut temp.layouts, &temp.theme);
+            let errors = &mut load_errors;
+            layouts.video = get_layout::<VideoLayoutItem>(t, "video", theme, errors);
+            layouts.seen_video = get_layout::<VideoLayoutItem>(t, "seen-video", theme, errors);
+            layouts.channel = get_layout::<ChannelLayoutItem>(t, "channel", theme, errors);
+            layouts.comment = get_layout::<CommentLayoutItem>(t, "comment", theme, errors);
+        }
+
+        if let Some(Value::Table(t)) = config.get("account") {
+            let password = match t.get("password") {
+                Some(Value::String(p)) => Some(p.clone()),
+                Some(_) => {
+                    load_errors.push(ConfigLoadError::NotAString("account: password".into()));
+                    None
+                }
+                None => None,
+            };
+            match t.get("username") {
+                Some(Value::String(username)) => {
+                    temp.account = Some(AccountConf {
+                        username: username.clone(),
+                        password,
+                        password_command: get_hook(t, "password-command", &mut load_errors),
+                    })
+                }
+                Some(_) => {
+                    load_errors.push(ConfigLoadError::NotAString("account: username".into()))
+                }
+                None => {}
+            }
+        }
+
         temp.torrent = torrent.map(|t| (t, false));
 
         (temp, load_errors)
@@ -572,10 +1192,22 @@ impl Config {
             self.player.use_raw_urls = true;
         }
 
+        if args.get_flag("audio-only") {
+            self.player.audio_only = true;
+        }
+
         if args.get_flag("select-quality") {
             self.select_quality = true;
         }
 
+        if args.get_flag("private") {
+            self.private = true;
+        }
+
+        if args.get_flag("tui") {
+            self.tui = true;
+        }
+
         if args.get_flag("color") {
             self.colors = true;
         } else if args.get_flag("no-color") {
@@ -585,28 +1217,121 @@ impl Config {
         load_errors
     }
 
+    fn profile(&self) -> Option<&PlayerProfile> {
+        self.active_profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+    }
+
     pub fn player(&self) -> &str {
-        match &self.torrent {
-            Some((tor, true)) => &tor.client,
+        match (&self.torrent, self.profile()) {
+            (Some((tor, true)), _) => &tor.client,
+            (_, Some(profile)) => &profile.client,
             _ => &self.player.client,
         }
     }
 
     pub fn use_raw_url(&self) -> bool {
-        self.player.use_raw_urls
+        self.profile()
+            .map_or(self.player.use_raw_urls, |p| p.use_raw_urls)
     }
 
     pub fn prefer_hls(&self) -> bool {
-        self.player.prefer_hls
+        self.profile()
+            .map_or(self.player.prefer_hls, |p| p.prefer_hls)
     }
 
     pub fn player_args(&self) -> &Vec<String> {
-        match &self.torrent {
-            Some((tor, true)) => &tor.args,
+        match (&self.torrent, self.profile()) {
+            (Some((tor, true)), _) => &tor.args,
+            (_, Some(profile)) => &profile.args,
             _ => &self.player.args,
         }
     }
 
+    /// Profile selected by the rules for a video
+    pub fn profile_for(&self, host: &str, live: bool) -> Option<&str> {
+        self.player_rules
+            .iter()
+            .find(|r| r.matches(host, live))
+            .map(|r| r.profile.as_str())
+    }
+
+    pub fn has_profile(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    pub fn profile_names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Use a player profile instead of the default player, until it is set back to None
+    pub fn set_profile(&mut self, name: Option<String>) {
+        self.active_profile = name;
+    }
+
+    /// Commands defined in the config, sorted by name
+    pub fn custom_commands(&self) -> &[CustomCommand] {
+        &self.commands
+    }
+
+    pub fn custom_command(&self, name: &str) -> Option<&CustomCommand> {
+        self.commands.iter().find(|c| c.name == name)
+    }
+
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    pub fn resolution_policy(&self) -> ResolutionPolicy {
+        self.player.resolution
+    }
+
+    /// Whether the playback is followed through the IPC of mpv
+    pub fn use_ipc(&self) -> bool {
+        match self.player.ipc {
+            _ if !IPC_SUPPORTED || self.use_torrent() || self.detach() => false,
+            Support::Auto => is_mpv(self.player()),
+            Support::Enable => true,
+            Support::Disable => false,
+        }
+    }
+
+    /// Whether the queue is given to the player as a single M3U playlist
+    pub fn use_playlists(&self) -> bool {
+        match self.player.playlists {
+            _ if self.use_torrent() => false,
+            Support::Auto => supports_playlists(self.player()),
+            Support::Enable => true,
+            Support::Disable => false,
+        }
+    }
+
+    /// Whether the players run in the background
+    pub fn detach(&self) -> bool {
+        self.player.detach
+    }
+
+    /// Share of a video that needs to be watched for it to be marked as seen
+    pub fn watched_threshold(&self) -> f64 {
+        self.player.watched_threshold
+    }
+
+    pub fn audio_only(&self) -> bool {
+        self.player.audio_only
+    }
+
+    /// Extra arguments passed to the player when playing audio only
+    pub fn audio_only_args(&self) -> &[String] {
+        match (&self.torrent, self.profile()) {
+            (Some((_, true)), _) => &[],
+            (_, Some(profile)) => &profile.audio_only_args,
+            _ => &self.player.audio_only_args,
+        }
+    }
+
     pub fn instance(&self) -> &str {
         &self.instance
     }
@@ -619,6 +1344,18 @@ impl Config {
         matches!(self.torrent, Some((_, true)))
     }
 
+    pub fn download_dir(&self) -> Option<&Path> {
+        self.download.directory.as_deref()
+    }
+
+    pub fn download_parallel(&self) -> usize {
+        self.download.parallel
+    }
+
+    pub fn sidecars(&self) -> SidecarConf {
+        self.download.sidecars
+    }
+
     pub fn max_hist_lines(&self) -> usize {
         self.max_hist_lines
     }
@@ -647,9 +1384,192 @@ impl Config {
         self.user_agent.clone()
     }
 
+    /// Whether the session was started with `--private`
+    pub fn private(&self) -> bool {
+        self.private
+    }
+
+    pub fn tui(&self) -> bool {
+        self.tui
+    }
+
     pub fn is_search_engine(&self) -> bool {
         self.is_search_engine
     }
+
+    /// Block a channel or an account by its handle, or an instance by its host
+    ///
+    /// Returns false if it already was blocked
+    pub fn block(&mut self, kind: BlockKind, name: String) -> bool {
+        match kind {
+            BlockKind::Instance => {
+                if self.is_allowlist {
+                    self.listed_instances.remove(&name)
+                } else {
+                    self.listed_instances.insert(name)
+                }
+            }
+            BlockKind::Channel => self.blocklist.channels.insert(name),
+            BlockKind::Account => self.blocklist.accounts.insert(name),
+        }
+    }
+
+    /// Remove a channel, an account or a keyword from the blocklist, or unblock an instance
+    ///
+    /// Returns false if it wasn't blocked
+    pub fn unblock(&mut self, entry: &str) -> bool {
+        if self.blocklist.channels.remove(entry) || self.blocklist.accounts.remove(entry) {
+            return true;
+        }
+        let keywords = self.blocklist.keywords.len();
+        self.blocklist.keywords.retain(|k| k.as_str() != entry);
+        if self.blocklist.keywords.len() != keywords {
+            return true;
+        }
+
+        // Handles contain an @, the hosts don't
+        if entry.contains('@') || self.is_blocked(entry).is_none() {
+            false
+        } else if self.is_allowlist {
+            // Allowing it wouldn't unblock it
+            !self.in_blocklist_files(entry) && self.listed_instances.insert(entry.to_owned())
+        } else {
+            self.listed_instances.remove(entry)
+        }
+    }
+
+    fn instance_reason(&self, host: &str) -> Option<String> {
+        block_reason(
+            self.is_allowlist ^ self.listed_instances.contains(host),
+            &self.blocklist_files.instances,
+            host,
+        )
+        .or_else(|| muted_reason(&self.muted_instances, host))
+    }
+
+    fn channel_reason(&self, handle: &str) -> Option<String> {
+        block_reason(
+            self.blocklist.channels.contains(handle),
+            &self.blocklist_files.channels,
+            handle,
+        )
+    }
+
+    fn account_reason(&self, handle: &str) -> Option<String> {
+        block_reason(
+            self.blocklist.accounts.contains(handle),
+            &self.blocklist_files.accounts,
+            handle,
+        )
+        .or_else(|| muted_reason(&self.muted_accounts, handle))
+    }
+
+    pub fn layouts(&self) -> &LayoutConf {
+        &self.layouts
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn account(&self) -> Option<&AccountConf> {
+        self.account.as_ref()
+    }
+
+    /// Replace the mirror of the mute lists of the account
+    pub fn set_muted(&mut self, instances: Vec<String>, accounts: Vec<String>) {
+        self.muted_instances = instances.into_iter().collect();
+        self.muted_accounts = accounts.into_iter().collect();
+    }
+
+    /// Returns false if it was already muted
+    pub fn mute(&mut self, kind: BlockKind, name: String) -> bool {
+        match kind {
+            BlockKind::Instance => self.muted_instances.insert(name),
+            BlockKind::Channel | BlockKind::Account => self.muted_accounts.insert(name),
+        }
+    }
+
+    /// Returns false if it wasn't muted
+    pub fn unmute(&mut self, name: &str) -> bool {
+        self.muted_instances.remove(name) || self.muted_accounts.remove(name)
+    }
+
+    /// Title and sorted entries of each mute list
+    pub fn muted(&self) -> Vec<(&'static str, Vec<&str>)> {
+        vec![
+            ("Muted instances", sorted(&self.muted_instances)),
+            ("Muted accounts", sorted(&self.muted_accounts)),
+        ]
+    }
+
+    /// Blocklist files that were loaded with their number of entries
+    pub fn blocklist_files(&self) -> &[(PathBuf, usize)] {
+        &self.blocklist_files.loaded
+    }
+
+    /// Whether the entry comes from a blocklist file, in which case it can't be unblocked
+    pub fn in_blocklist_files(&self, entry: &str) -> bool {
+        let files = &self.blocklist_files;
+        files.instances.contains_key(entry)
+            || files.channels.contains_key(entry)
+            || files.accounts.contains_key(entry)
+    }
+
+    /// Title and sorted entries of each part of the blocklist
+    pub fn blocklists(&self) -> Vec<(&'static str, Vec<&str>)> {
+        vec![
+            (
+                if self.is_allowlist {
+                    "Allowed instances (all the others are blocked)"
+                } else {
+                    "Blocked instances"
+                },
+                sorted(&self.listed_instances),
+            ),
+            ("Blocked channels", sorted(&self.blocklist.channels)),
+            ("Blocked accounts", sorted(&self.blocklist.accounts)),
+            (
+                "Blocked keywords",
+                self.blocklist.keywords.iter().map(Regex::as_str).collect(),
+            ),
+        ]
+    }
+
+    /// Write the blocklists to the config file, keeping its formatting and comments
+    pub fn save_blocklists(&self) -> Result<(), io::Error> {
+        let path = self.path.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "No config directory available")
+        })?;
+        let content = match read_to_string(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            res => res?,
+        };
+        let mut doc: toml_edit::Document = content
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let keys = if self.is_allowlist {
+            ["allowlist", "whitelist"]
+        } else {
+            ["blocklist", "blacklist"]
+        };
+        let key = keys
+            .iter()
+            .find(|k| doc.get("instances").and_then(|t| t.get(k)).is_some())
+            .unwrap_or(&keys[0]);
+
+        let lists = self.blocklists();
+        edit::sync_array(&mut doc, "instances", key, &lists[0].1);
+        edit::sync_array(&mut doc, "blocklist", "channels", &lists[1].1);
+        edit::sync_array(&mut doc, "blocklist", "accounts", &lists[2].1);
+        edit::sync_array(&mut doc, "blocklist", "keywords", &lists[3].1);
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        write(path, doc.to_string())
+    }
 }
 
 impl Default for Config {
@@ -660,42 +1580,143 @@ impl Default for Config {
                 args: Vec::new(),
                 use_raw_urls: false,
                 prefer_hls: true,
+                audio_only: false,
+                audio_only_args: default_audio_only_args(),
+                resolution: ResolutionPolicy::default(),
+                ipc: Support::Auto,
+                watched_threshold: 0.9,
+                playlists: Support::Auto,
+                detach: false,
             },
+            profiles: HashMap::new(),
+            player_rules: Vec::new(),
+            active_profile: None,
+            commands: Vec::new(),
+            hooks: Hooks::default(),
             instance: "https://sepiasearch.org".to_string(),
             is_search_engine: true,
             torrent: None,
+            download: DownloadConf {
+                directory: None,
+                parallel: 2,
+                sidecars: SidecarConf {
+                    metadata: MetadataFormat::None,
+                    thumbnail: false,
+                    captions: false,
+                },
+            },
             user_agent: Some(USER_AGENT.into()),
             nsfw: NsfwBehavior::Tag,
             listed_instances: HashSet::new(),
             is_allowlist: false,
+            blocklist: ContentBlocklist::default(),
+            blocklist_files: BlocklistFiles::default(),
+            account: None,
+            muted_instances: HashSet::new(),
+            muted_accounts: HashSet::new(),
+            layouts: LayoutConf::default(),
+            theme: Theme::default(),
             edit_mode: EditMode::Emacs,
             browser: var("BROWSER").unwrap_or_else(|_| "firefox".to_string()),
             colors: true,
             select_quality: false,
             local: false,
+            private: false,
+            tui: false,
             max_hist_lines: 2000,
+            path: None,
         }
     }
 }
 
+fn sorted(set: &HashSet<String>) -> Vec<&str> {
+    let mut v: Vec<_> = set.iter().map(String::as_str).collect();
+    v.sort_unstable();
+    v
+}
+
 impl Blocklist<str> for Config {
     fn is_blocked(&self, instance: &str) -> Option<String> {
-        if self.is_allowlist ^ self.listed_instances.contains(instance) {
-            Some(instance.to_string())
+        self.instance_reason(instance)
+            .map(|reason| format!("{instance}{reason}"))
+    }
+}
+
+impl Blocklist<Video> for Config {
+    fn is_blocked(&self, video: &Video) -> Option<String> {
+        let channel = video.channel_handle();
+        let account = video.account_handle();
+        if let Some(reason) = self.instance_reason(video.host()) {
+            Some(format!("Blocked video from: {}{reason}", video.host()))
+        } else if let Some(reason) = self.channel_reason(&channel) {
+            Some(format!("Blocked channel: {channel}{reason}"))
+        } else if let Some(reason) = self.account_reason(&account) {
+            Some(format!("Blocked account: {account}{reason}"))
         } else {
-            None
+            self.blocklist
+                .keyword([video.name()].into_iter().chain(video.short_desc()))
         }
     }
+
+    fn hide_blocked(&self) -> bool {
+        self.blocklist.hide
+    }
 }
 
-impl Blocklist<peertube_api::Video> for Config {
-    fn is_blocked(&self, video: &peertube_api::Video) -> Option<String> {
-        if self.is_allowlist ^ self.listed_instances.contains(video.host()) {
-            Some(format!("Blocked video from: {}", video.host()))
+impl Blocklist<Channel> for Config {
+    fn is_blocked(&self, channel: &Channel) -> Option<String> {
+        let handle = channel.handle();
+        if let Some(reason) = self.instance_reason(channel.host()) {
+            Some(format!("Blocked channel from: {}{reason}", channel.host()))
+        } else if let Some(reason) = self.channel_reason(&handle) {
+            Some(format!("Blocked channel: {handle}{reason}"))
         } else {
-            None
+            self.blocklist.keyword(
+                [channel.display_name()]
+                    .into_iter()
+                    .chain(channel.description().as_deref()),
+            )
         }
     }
+
+    fn hide_blocked(&self) -> bool {
+        self.blocklist.hide
+    }
+}
+
+impl Blocklist<Comment> for Config {
+    fn is_blocked(&self, comment: &Comment) -> Option<String> {
+        let author = comment.author_handle();
+        if let Some(reason) = self.instance_reason(comment.author_host()) {
+            Some(format!(
+                "Blocked comment from: {}{reason}",
+                comment.author_host()
+            ))
+        } else if let Some(reason) = self.account_reason(&author) {
+            Some(format!("Blocked account: {author}{reason}"))
+        } else {
+            self.blocklist.keyword([comment.content()])
+        }
+    }
+
+    fn hide_blocked(&self) -> bool {
+        self.blocklist.hide
+    }
+}
+
+fn muted_reason(muted: &HashSet<String>, name: &str) -> Option<String> {
+    muted.contains(name).then(|| " (muted)".to_owned())
+}
+
+/// `None` if the item isn't blocked, otherwise the reason given by the blocklist file, if any
+fn block_reason(listed: bool, entries: &Entries, name: &str) -> Option<String> {
+    if listed {
+        return Some(String::new());
+    }
+    entries.get(name).map(|reason| match reason {
+        Some(reason) => format!(" ({reason})"),
+        None => String::new(),
+    })
 }
 
 fn concat(v: impl IntoIterator<Item = impl AsRef<str>>) -> String {
@@ -711,6 +1732,43 @@ fn concat(v: impl IntoIterator<Item = impl AsRef<str>>) -> String {
     concatenated
 }
 
+fn parse_player_rule(rule: &Value) -> Result<PlayerRule, ConfigLoadError> {
+    let t = rule
+        .as_table()
+        .ok_or_else(|| ConfigLoadError::BadPlayerRule("rules should be tables".to_owned()))?;
+    let profile = t
+        .get("profile")
+        .and_then(Value::as_str)
+        .ok_or_else(|| ConfigLoadError::BadPlayerRule("missing profile".to_owned()))?;
+    let host = match t.get("host") {
+        Some(Value::String(h)) => Some(h.clone()),
+        Some(_) => {
+            return Err(ConfigLoadError::BadPlayerRule(
+                "host should be a string".to_owned(),
+            ))
+        }
+        None => None,
+    };
+    let live = match t.get("live") {
+        Some(Value::Boolean(l)) => Some(*l),
+        Some(_) => {
+            return Err(ConfigLoadError::BadPlayerRule(
+                "live should be true or false".to_owned(),
+            ))
+        }
+        None => None,
+    };
+    Ok(PlayerRule {
+        host,
+        live,
+        profile: profile.to_owned(),
+    })
+}
+
+fn default_audio_only_args() -> Vec<String> {
+    vec!["--no-video".to_string()]
+}
+
 fn get_string_array(t: &Table, name: &str, load_errors: &mut Vec<ConfigLoadError>) -> Vec<String> {
     t.get(name)
         .and_then(|cmd| cmd.as_array())
@@ -730,6 +1788,42 @@ fn get_string_array(t: &Table, name: &str, load_errors: &mut Vec<ConfigLoadError
         .unwrap_or_default()
 }
 
+/// Template of a list, only kept if it is valid
+fn get_layout<I: InnerLayoutItem>(
+    t: &Table,
+    name: &'static str,
+    theme: &Theme,
+    load_errors: &mut Vec<ConfigLoadError>,
+) -> Option<String> {
+    match t.get(name) {
+        Some(Value::String(template)) => match parse_layout::<I>(template, theme) {
+            Ok(_) => Some(template.clone()),
+            Err(e) => {
+                load_errors.push(ConfigLoadError::InvalidLayout(name, e));
+                None
+            }
+        },
+        Some(_) => {
+            load_errors.push(ConfigLoadError::NotAString(format!("layout: {name}")));
+            None
+        }
+        None => None,
+    }
+}
+
+/// A hook is either a program or an array with the program and its arguments
+fn get_hook(t: &Table, name: &str, load_errors: &mut Vec<ConfigLoadError>) -> Vec<String> {
+    match t.get(name) {
+        Some(Value::String(s)) => vec![s.to_owned()],
+        Some(Value::Array(_)) => get_string_array(t, name, load_errors),
+        Some(_) => {
+            load_errors.push(ConfigLoadError::NotAString(format!("hooks: {name}")));
+            Vec::new()
+        }
+        None => Vec::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -747,10 +1841,112 @@ mod tests {
         assert_eq!(config.instance(), "https://skeptikon.fr");
         assert_eq!(config.browser(), "qutebrowser");
         assert!(config.is_blocked("peertube.social").is_some());
+        assert!(config
+            .blocklist
+            .channels
+            .contains("spam_channel@tube.example.com"));
+        assert!(config
+            .blocklist
+            .accounts
+            .contains("spammer@tube.example.com"));
+        assert_eq!(
+            config.blocklist.keyword(["[REUPLOAD] A talk"]),
+            Some("Blocked keyword: ^\\[REUPLOAD\\]".to_string())
+        );
+        assert_eq!(
+            config.blocklist.keyword(["A talk", "Get FREE V-Bucks"]),
+            Some("Blocked keyword: (?i)free v-bucks".to_string())
+        );
+        assert_eq!(config.blocklist.keyword(["A talk [REUPLOAD]"]), None);
+        assert_eq!(config.blocklist.hide, true);
+        assert_eq!(
+            config.layouts(),
+            &LayoutConf {
+                video: Some("{name:blue} |{channel:green} |[{duration:yellow}] |{views} |{published} |{nsfw:red}".into()),
+                ..LayoutConf::default()
+            }
+        );
+        let mut theme = Theme::preset("light").unwrap();
+        theme.title = Style::parse("bold+#268bd2").unwrap();
+        theme.views = Style::parse("64").unwrap();
+        assert_eq!(config.theme(), &theme);
+        assert_eq!(
+            config.account(),
+            Some(&AccountConf {
+                username: "viewer".into(),
+                password: None,
+                password_command: vec!["pass".into(), "peertube/skeptikon.fr".into()],
+            })
+        );
         assert_eq!(config.use_raw_url(), true);
         assert_eq!(config.select_quality(), true);
         assert_eq!(config.prefer_hls(), false);
+        assert_eq!(config.audio_only(), false);
+        assert_eq!(config.use_ipc(), false);
+        assert_eq!(config.watched_threshold(), 0.75);
+        assert_eq!(config.use_playlists(), true);
+        assert_eq!(config.detach(), true);
+        assert_eq!(config.profile_names(), vec!["streamlink", "tv"]);
+        assert_eq!(
+            config.hooks(),
+            &Hooks {
+                on_play: vec!["notify-send".into(), "Playing".into()],
+                on_download_finished: vec!["/home/user/bin/scrobble".into()],
+                on_error: Vec::new(),
+                on_quit: Vec::new(),
+            }
+        );
+        assert_eq!(
+            config.custom_commands(),
+            &[
+                CustomCommand {
+                    name: "cast".into(),
+                    command: "catt cast {raw_url}".into(),
+                    description: Some("Play on the TV".into()),
+                },
+                CustomCommand {
+                    name: "wiki".into(),
+                    command: "echo {watch_url} {title} >> ~/notes/videos.md".into(),
+                    description: None,
+                },
+            ]
+        );
+        assert_eq!(config.profile_for("tube.example.org", false), Some("tv"));
+        assert_eq!(
+            config.profile_for("tube.example.org", true),
+            Some("streamlink")
+        );
+        assert_eq!(config.profile_for("skeptikon.fr", false), None);
+        config.set_profile(Some("tv".to_string()));
+        assert_eq!(config.player(), "catt");
+        assert_eq!(*config.player_args(), vec!["cast"]);
+        assert_eq!(config.use_raw_url(), true);
+        assert!(config.audio_only_args().is_empty());
+        config.set_profile(None);
+        assert_eq!(config.player(), "mpv");
+        assert_eq!(
+            config.resolution_policy(),
+            ResolutionPolicy {
+                preferred: Some(720),
+                max: Some(1080),
+                prefer: Prefer::Closest,
+            }
+        );
+        assert_eq!(
+            config.audio_only_args(),
+            ["--no-video", "--force-window=no"]
+        );
         assert_eq!(config.edit_mode(), EditMode::Vi);
+        assert_eq!(config.download_dir(), Some(Path::new("/tmp/peertube")));
+        assert_eq!(config.download_parallel(), 3);
+        assert_eq!(
+            config.sidecars(),
+            SidecarConf {
+                metadata: MetadataFormat::Nfo,
+                thumbnail: true,
+                captions: true,
+            }
+        );
         assert_eq!(
             config.user_agent(),
             Some("Mozilla/5.0 (X11; Linux x86_64; rv:79.0) Gecko/20100101 Firefox/79.0".into())
@@ -766,12 +1962,18 @@ mod tests {
                 "--instance=args.ploud.fr",
                 "--use-raw-urls",
                 "--let-nsfw",
+                "--audio-only",
+                "--private",
+                "--tui",
                 "-s",
             ])
             .unwrap();
         errors = config.update_with_args(matches);
         assert_eq!(errors.len(), 0);
+        assert_eq!(config.private(), true);
+        assert_eq!(config.tui(), true);
         assert_eq!(config.nsfw(), NsfwBehavior::Let);
+        assert_eq!(config.audio_only(), true);
         assert_eq!(config.player(), "args-player");
         assert_eq!(*config.player_args(), vec!["--volume=30", "--no-video"]);
         assert_eq!(config.instance(), "https://args.ploud.fr");
@@ -780,6 +1982,85 @@ mod tests {
         assert_eq!(config.edit_mode(), EditMode::Vi);
     }
 
+    #[test]
+    fn runtime_blocklist() {
+        let mut config = Config::default();
+        assert!(config.block(BlockKind::Instance, "tube.example.com".into()));
+        assert!(!config.block(BlockKind::Instance, "tube.example.com".into()));
+        assert!(config.block(BlockKind::Account, "spammer@tube.example.com".into()));
+        assert!(config.is_blocked("tube.example.com").is_some());
+        assert!(config.unblock("spammer@tube.example.com"));
+        assert!(!config.unblock("spammer@tube.example.com"));
+        assert!(config.unblock("tube.example.com"));
+        assert!(config.is_blocked("tube.example.com").is_none());
+
+        // With an allowlist, blocking an instance removes it from the list
+        config.is_allowlist = true;
+        assert!(config.unblock("tube.example.com"));
+        assert!(config.is_blocked("tube.example.com").is_none());
+        assert!(config.block(BlockKind::Instance, "tube.example.com".into()));
+        assert!(config.is_blocked("tube.example.com").is_some());
+
+        // Mutes of the account block too, without being part of the blocklist
+        config.is_allowlist = false;
+        config.set_muted(vec!["spam.example.com".into()], Vec::new());
+        assert!(config.mute(BlockKind::Account, "spammer@tube.example.com".into()));
+        assert_eq!(
+            config.is_blocked("spam.example.com"),
+            Some("spam.example.com (muted)".to_owned())
+        );
+        assert!(!config.unblock("spam.example.com"));
+        assert!(config.unmute("spam.example.com"));
+        assert!(config.is_blocked("spam.example.com").is_none());
+        assert_eq!(
+            config.muted(),
+            vec![
+                ("Muted instances", vec![]),
+                ("Muted accounts", vec!["spammer@tube.example.com"])
+            ]
+        );
+    }
+
+    #[test]
+    fn blocklist_files() {
+        let dir = std::env::temp_dir().join(format!("ptv-blocklist-test-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        write(
+            dir.join("config.toml"),
+            "[instances]\nblocklist-files = [\"domains.csv\", \"missing.txt\"]\n",
+        )
+        .unwrap();
+        write(
+            dir.join("domains.csv"),
+            "#domain,#severity,#public_comment\nspam.example.com,suspend,Spam\ntube.example.com,silence,\n",
+        )
+        .unwrap();
+        let (mut config, errors) = Config::from_config_file(&dir.join("config.toml"), false);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            errors.as_slice(),
+            [ConfigLoadError::UnreadableBlocklist(_, _)]
+        ));
+        assert_eq!(config.blocklist_files(), &[(dir.join("domains.csv"), 2)]);
+        assert_eq!(
+            config.is_blocked("spam.example.com"),
+            Some("spam.example.com (Spam)".to_owned())
+        );
+        assert_eq!(
+            config.is_blocked("tube.example.com"),
+            Some("tube.example.com".to_owned())
+        );
+        assert!(config.in_blocklist_files("tube.example.com"));
+        assert!(config.blocklists()[0].1.is_empty());
+
+        // With an allowlist, an instance of a blocklist file can't be unblocked
+        config.is_allowlist = true;
+        assert!(!config.unblock("tube.example.com"));
+        assert!(config.blocklists()[0].1.is_empty());
+        assert!(config.is_blocked("tube.example.com").is_some());
+    }
+
     #[test]
     fn torrent_options() {
         let path = PathBuf::from("src/cli/full_config.toml");
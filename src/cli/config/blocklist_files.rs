@@ -0,0 +1,169 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Entries of a shared blocklist, with the reason given by the list if there is one
+pub type Entries = HashMap<String, Option<String>>;
+
+/// Entries loaded from the blocklist files, they are never written back to the config
+#[derive(Debug, Default, PartialEq)]
+pub struct BlocklistFiles {
+    pub instances: Entries,
+    pub channels: Entries,
+    pub accounts: Entries,
+    /// Files that were loaded with their number of entries
+    pub loaded: Vec<(PathBuf, usize)>,
+}
+
+/// Load a blocklist and add its entries to `entries`
+pub fn load(
+    path: &Path,
+    entries: &mut Entries,
+    loaded: &mut Vec<(PathBuf, usize)>,
+) -> Result<(), io::Error> {
+    let parsed = parse(&read_to_string(path)?);
+    loaded.push((path.to_owned(), parsed.len()));
+    entries.extend(parsed);
+    Ok(())
+}
+
+/// Parse a list in one of the supported formats:
+///
+/// - one entry per line, with `#` starting comments
+/// - CSV with a header, as exported by Mastodon (`#domain,#severity,…,#public_comment,…`)
+/// - JSON with a `data` array of `{"value": …}` objects, as used by PeerTube's auto-blocklists
+fn parse(content: &str) -> Vec<(String, Option<String>)> {
+    if content.trim_start().starts_with('{') {
+        return parse_json(content);
+    }
+
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .peekable();
+    let header: Option<Vec<String>> = lines
+        .peek()
+        .filter(|l| l.contains(',') && l.trim_start_matches('#').starts_with("domain"))
+        .map(|l| {
+            split_csv(l)
+                .into_iter()
+                .map(|c| c.trim_start_matches('#').to_owned())
+                .collect()
+        });
+    if header.is_some() {
+        lines.next();
+    }
+    let column = |name: &str| {
+        header
+            .as_ref()
+            .and_then(|h| h.iter().position(|c| c == name))
+    };
+    let (domain, severity, comment) = (
+        column("domain").unwrap_or(0),
+        column("severity"),
+        column("public_comment"),
+    );
+
+    lines
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| {
+            let fields = split_csv(l);
+            // Mastodon exports can list domains that are only tracked, not blocked
+            if severity.and_then(|s| fields.get(s)).map(String::as_str) == Some("noop") {
+                return None;
+            }
+            let entry = fields.get(domain)?.trim().to_owned();
+            let reason = comment
+                .and_then(|c| fields.get(c))
+                .map(|r| r.trim().to_owned())
+                .filter(|r| !r.is_empty());
+            (!entry.is_empty()).then_some((entry, reason))
+        })
+        .collect()
+}
+
+fn parse_json(content: &str) -> Vec<(String, Option<String>)> {
+    let json: Value = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(_) => return Vec::new(),
+    };
+    json.get("data")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| Some((e.get("value")?.as_str()?.to_owned(), None)))
+        .collect()
+}
+
+/// Split a line of CSV, fields may be quoted with `"` and contain `""` for a quote
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(name: &str, reason: Option<&str>) -> (String, Option<String>) {
+        (name.to_owned(), reason.map(str::to_owned))
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            parse("# Spam instances\nspam.example.com\n\n  tube.example.com \n"),
+            vec![
+                entry("spam.example.com", None),
+                entry("tube.example.com", None)
+            ]
+        );
+        assert_eq!(
+            parse(
+                "#domain,#severity,#reject_media,#reject_reports,#public_comment,#obfuscate\n\
+                 spam.example.com,suspend,false,false,\"Spam, lots of it\",false\n\
+                 tracked.example.com,noop,false,false,,false\n\
+                 tube.example.com,silence,true,false,,false\n"
+            ),
+            vec![
+                entry("spam.example.com", Some("Spam, lots of it")),
+                entry("tube.example.com", None)
+            ]
+        );
+        assert_eq!(
+            parse(
+                r#"{"data": [{"value": "spammer@tube.example.com", "updatedAt": "2023-10-16T10:00:00.000Z"}]}"#
+            ),
+            vec![entry("spammer@tube.example.com", None)]
+        );
+        assert_eq!(split_csv(r#"a,"b ""c""",d"#), vec!["a", "b \"c\"", "d"]);
+    }
+}
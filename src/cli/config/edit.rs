@@ -0,0 +1,81 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use toml_edit::{table, value, Array, Document};
+
+/// Make the array `key` of the table `table_name` contain exactly `values`, keeping the formatting and comments
+///
+/// The values already present keep their place and the new ones are appended
+/// The array isn't created if there is nothing to put in it
+pub fn sync_array(doc: &mut Document, table_name: &str, key: &str, values: &[&str]) {
+    let exists = doc.get(table_name).and_then(|t| t.get(key)).is_some();
+    if values.is_empty() && !exists {
+        return;
+    }
+
+    let item = &mut doc.entry(table_name).or_insert(table())[key];
+    match item.as_array_mut() {
+        Some(array) => {
+            // The new first value takes the place of the previous one
+            let first_prefix = array.get(0).and_then(|v| v.decor().prefix().cloned());
+            array.retain(|v| v.as_str().map_or(true, |s| values.contains(&s)));
+            if let (Some(prefix), Some(first)) = (first_prefix, array.get_mut(0)) {
+                first.decor_mut().set_prefix(prefix);
+            }
+            for v in values {
+                if !array.iter().any(|a| a.as_str() == Some(v)) {
+                    array.push(*v);
+                }
+            }
+        }
+        None => *item = value(values.iter().copied().collect::<Array>()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sync() {
+        let mut doc: Document = "\
+# Instances
+[instances]
+main = \"skeptikon.fr\" # The main one
+blocklist = [\"peertube.social\", \"tube.example.com\"]
+"
+        .parse()
+        .unwrap();
+        sync_array(
+            &mut doc,
+            "instances",
+            "blocklist",
+            &["tube.example.com", "spam.example.com"],
+        );
+        sync_array(
+            &mut doc,
+            "blocklist",
+            "channels",
+            &["spam@tube.example.com"],
+        );
+        sync_array(&mut doc, "blocklist", "accounts", &[]);
+        assert_eq!(
+            doc.to_string(),
+            "\
+# Instances
+[instances]
+main = \"skeptikon.fr\" # The main one
+blocklist = [\"tube.example.com\", \"spam.example.com\"]
+
+[blocklist]
+channels = [\"spam@tube.example.com\"]
+"
+        );
+    }
+}
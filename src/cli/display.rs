@@ -9,8 +9,10 @@
 use peertube_api::{channels::Channel, Comment, Resolution, Video};
 
 use super::{
-    config::Blocklist,
+    config::{Blocklist, CustomCommand, LayoutConf},
+    downloads::{JobInfo, JobStatus},
     history::{History, HistoryT},
+    stats::{Row, Stats},
 };
 use std::fmt::{self, Debug};
 use terminal_size::{terminal_size, Width};
@@ -20,14 +22,22 @@ use textwrap::fill;
 use std::cmp;
 use std::fmt::Write;
 use std::sync::Arc;
+use std::time::Duration;
 
-mod layout;
+pub mod layout;
 use layout::{
-    default_channel_layouts, default_comment_layouts, default_video_layouts, ChannelLayoutItem,
-    CommentLayoutItem, InnerLayoutItem, LayoutItem, VideoLayoutItem,
+    parse_layout, ChannelLayoutItem, CommentLayoutItem, InnerLayoutItem, LayoutItem,
+    VideoLayoutItem, DEFAULT_CHANNEL_LAYOUT, DEFAULT_COMMENT_LAYOUT, DEFAULT_SEEN_VIDEO_LAYOUT,
+    DEFAULT_VIDEO_LAYOUT,
 };
 
+pub mod fit;
+pub mod theme;
+use fit::{Fit, Segment};
+use theme::Theme;
+
 mod helpers;
+pub use helpers::pretty_duration;
 use helpers::*;
 
 use unicode_width::UnicodeWidthStr;
@@ -48,6 +58,7 @@ pub struct Display {
     seen_video_layout: Vec<LayoutItem<VideoLayoutItem>>,
     channel_layout: Vec<LayoutItem<ChannelLayoutItem>>,
     comment_layout: Vec<LayoutItem<CommentLayoutItem>>,
+    theme: Theme,
 }
 
 #[derive(Debug)]
@@ -75,19 +86,72 @@ impl<T: color::Color> fmt::Display for MaybeColor<T> {
     }
 }
 
-impl Display {
-    pub fn new(colors: bool) -> Display {
-        let (video_layout, seen_video_layout) = default_video_layouts();
+/// The templates of the config were checked when loading it
+fn layout<I: InnerLayoutItem>(
+    template: &Option<String>,
+    default: &str,
+    theme: &Theme,
+) -> Vec<LayoutItem<I>> {
+    template
+        .as_deref()
+        .and_then(|t| parse_layout(t, theme).ok())
+        .unwrap_or_else(|| parse_layout(default, theme).expect("Invalid default layout"))
+}
+
+fn line(buffer: &mut String, c: char, cols: usize) {
+    buffer.extend(std::iter::repeat(c).take(cols));
+    buffer.push('\n');
+}
+
+fn centered(buffer: &mut String, s: &str, cols: usize) {
+    let len = s.chars().count();
+    if len <= cols {
+        buffer.push_str(&" ".repeat((cols - len) / 2));
+    }
+    buffer.push_str(s);
+    buffer.push('\n');
+}
+
+fn join_rows(rows: Vec<(usize, String)>) -> String {
+    rows.into_iter().map(|(_, row)| row + "\n").collect()
+}
+
+/// Fits of the fields of each column of a layout
+fn column_fits<I: InnerLayoutItem>(layout: &[LayoutItem<I>]) -> Vec<Vec<Fit>> {
+    let mut columns = vec![Vec::new()];
+    for item in layout {
+        if item.is_align() {
+            columns.push(Vec::new());
+        } else if !item.is_style() {
+            columns
+                .last_mut()
+                .expect("Internal error: no column")
+                .push(item.fit());
+        }
+    }
+    columns
+}
 
+impl Display {
+    pub fn new(colors: bool, layouts: &LayoutConf, theme: &Theme) -> Display {
         Display {
             colors,
-            video_layout,
-            seen_video_layout,
-            channel_layout: default_channel_layouts(),
-            comment_layout: default_comment_layouts(),
+            video_layout: layout(&layouts.video, DEFAULT_VIDEO_LAYOUT, theme),
+            seen_video_layout: layout(&layouts.seen_video, DEFAULT_SEEN_VIDEO_LAYOUT, theme),
+            channel_layout: layout(&layouts.channel, DEFAULT_CHANNEL_LAYOUT, theme),
+            comment_layout: layout(&layouts.comment, DEFAULT_COMMENT_LAYOUT, theme),
+            theme: if colors {
+                theme.clone()
+            } else {
+                Theme::plain()
+            },
         }
     }
 
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
     /// Display a list of video results
     pub fn video_list(
         &self,
@@ -95,92 +159,143 @@ impl Display {
         history: &History,
         blocklist: &impl Blocklist<Video>,
     ) {
-        self.list(
+        print!("{}", self.render_video_list(videos, history, blocklist));
+    }
+
+    pub fn render_video_list(
+        &self,
+        videos: &[Arc<Video>],
+        history: &History,
+        blocklist: &impl Blocklist<Video>,
+    ) -> String {
+        join_rows(self.video_rows(videos, history, blocklist, col_size()))
+    }
+
+    pub fn video_rows(
+        &self,
+        videos: &[Arc<Video>],
+        history: &History,
+        blocklist: &impl Blocklist<Video>,
+        cols: usize,
+    ) -> Vec<(usize, String)> {
+        self.list_rows(
             videos,
             history,
             blocklist,
             &self.video_layout,
             &self.seen_video_layout,
-        );
+            cols,
+        )
+    }
+
+    pub fn render_channel_list(
+        &self,
+        channels: &[Arc<Channel>],
+        blocklist: &impl Blocklist<Channel>,
+    ) -> String {
+        join_rows(self.channel_rows(channels, blocklist, col_size()))
     }
 
-    pub fn channel_list(&self, channels: &[Arc<Channel>], _: &History, _: &impl Blocklist<Video>) {
-        self.list(
+    pub fn channel_rows(
+        &self,
+        channels: &[Arc<Channel>],
+        blocklist: &impl Blocklist<Channel>,
+        cols: usize,
+    ) -> Vec<(usize, String)> {
+        self.list_rows(
             channels,
             &(),
-            &(),
+            blocklist,
             &self.channel_layout,
             &self.channel_layout,
-        );
+            cols,
+        )
+    }
+
+    pub fn render_comment_list(
+        &self,
+        comments: &[Arc<Comment>],
+        blocklist: &impl Blocklist<Comment>,
+    ) -> String {
+        join_rows(self.comment_rows(comments, blocklist, col_size()))
     }
 
-    pub fn comment_list(&self, comments: &[Arc<Comment>]) {
-        self.list(
+    pub fn comment_rows(
+        &self,
+        comments: &[Arc<Comment>],
+        blocklist: &impl Blocklist<Comment>,
+        cols: usize,
+    ) -> Vec<(usize, String)> {
+        self.list_rows(
             comments,
             &(),
-            &(),
+            blocklist,
             &self.comment_layout,
             &self.comment_layout,
-        );
+            cols,
+        )
     }
 
-    fn list<I, D, B, H>(
+    /// Render the items of a list fitted in `cols` columns, with their index in `contents`
+    ///
+    /// The hidden blocked items are skipped
+    fn list_rows<I, D, B, H>(
         &self,
         contents: &[Arc<D>],
         history: &H,
         blocklist: &B,
         layout: &[LayoutItem<I>],
         seen_layout: &[LayoutItem<I>],
-    ) where
+        cols: usize,
+    ) -> Vec<(usize, String)>
+    where
         B: Blocklist<D>,
         H: HistoryT<D>,
         I: InnerLayoutItem<Data = D>,
     {
-        let mut content_parts = Vec::new();
-        let mut alignments_total = vec![
-            0;
-            cmp::max(
-                seen_layout.iter().filter(|i| i.is_align()).count(),
-                layout.iter().filter(|i| i.is_align()).count()
-            )
-        ];
-        for v in contents {
-            let mut tmp_str = Vec::new();
-            let mut tmp_align = Vec::new();
-
-            if blocklist.is_blocked(v).is_some() {
-                content_parts.push((tmp_str, tmp_align));
-                continue;
-            }
-
-            let mut align_off: usize = 0;
-            let mut align_id = 0;
-            let layout_iter = if history.is_viewed(&**v) {
-                seen_layout.iter()
-            } else {
-                layout.iter()
-            };
+        let columns = fit::columns(&[column_fits(layout), column_fits(seen_layout)]);
+        let mut rows: Vec<Option<Vec<Segment>>> = contents
+            .iter()
+            .map(|v| {
+                if blocklist.is_blocked(v).is_some() {
+                    return None;
+                }
 
-            for item in layout_iter {
-                if !item.is_align() && !item.is_style() {
-                    let dsp = item.display(v);
-                    let s: &str = &dsp;
-                    align_off += UnicodeWidthStr::width(s);
-                    tmp_str.push(dsp);
-                } else if item.is_align() {
-                    if alignments_total[align_id] < align_off {
-                        alignments_total[align_id] = align_off;
+                let seen = history.is_viewed(&**v);
+                let layout = if seen { seen_layout } else { layout };
+                let mut segments = vec![Vec::new()];
+                for item in layout {
+                    if item.is_align() {
+                        segments.push(Vec::new());
+                    } else if !item.is_style() {
+                        let mut dsp = item.display(v, seen);
+                        if item.fit() == Fit::Wrap {
+                            dsp = fill(&dsp, cols);
+                        }
+                        segments
+                            .last_mut()
+                            .expect("Internal error: no segment")
+                            .push((dsp, item.fit()));
                     }
-                    tmp_align.push(align_off);
-                    align_off = 0;
-                    align_id += 1;
                 }
+                Some(segments)
+            })
+            .collect();
+        let widths = fit::fit(
+            &mut rows,
+            &columns,
+            display_length(contents.len()) + 2,
+            cols,
+        );
+
+        let mut rendered = Vec::new();
+        for (id, row) in rows.into_iter().enumerate() {
+            let blocked = blocklist.is_blocked(&contents[id]);
+            if blocked.is_some() && blocklist.hide_blocked() {
+                continue;
             }
-            content_parts.push((tmp_str, tmp_align));
-        }
 
-        let mut buffer = String::new();
-        for (id, parts) in content_parts.into_iter().enumerate() {
+            let mut buffer = String::new();
             buffer.push_str(&(id + 1).to_string());
             buffer.push_str(
                 &" ".to_string()
@@ -188,53 +303,54 @@ impl Display {
             );
             buffer.push_str(": ");
 
-            if let Some(reason) = blocklist.is_blocked(&contents[id]) {
-                writeln!(
-                    buffer,
-                    "{}{}{}",
-                    fg_color(color::Red, self.colors),
-                    reason,
-                    fg_color(color::Reset, self.colors)
-                )
-                .expect("Formatting failed unexpectedly");
-                continue;
-            }
+            let segments = match (blocked, row) {
+                (None, Some(segments)) => segments,
+                (reason, _) => {
+                    write!(
+                        buffer,
+                        "{}",
+                        self.theme.error.paint(reason.unwrap_or_default())
+                    )
+                    .expect("Formatting failed unexpectedly");
+                    rendered.push((id, buffer));
+                    continue;
+                }
+            };
 
-            let mut layout_align_it = alignments_total.iter();
             let layout_it = if history.is_viewed(&*contents[id]) {
                 seen_layout.iter()
             } else {
                 layout.iter()
             };
-            let mut parts_it = parts.0.iter();
-            let mut parts_align_it = parts.1.iter();
+            let mut column = 0;
+            let mut fields = segments[0].iter();
 
             for item in layout_it {
                 if item.is_align() {
-                    let spacing = layout_align_it
-                        .next()
-                        .expect("Internal error: align smaller than expected")
-                        - parts_align_it
-                            .next()
-                            .expect("Internal error: align smaller than expected");
-                    buffer.push_str(&" ".to_string().repeat(spacing));
+                    if let Some(w) = widths[column] {
+                        buffer
+                            .push_str(&" ".repeat(w.saturating_sub(fit::width(&segments[column]))));
+                    }
+                    column += 1;
+                    fields = segments[column].iter();
                 } else if item.is_style() {
                     if self.colors {
                         buffer.push_str(&item.display_as_style());
                     }
                 } else {
-                    buffer.push_str(
-                        parts_it
-                            .next()
-                            .expect("Internal Error: parts smaller than alignment"),
-                    );
+                    let (field, _) = fields
+                        .next()
+                        .expect("Internal Error: parts smaller than alignment");
+                    if widths[column].is_some() {
+                        buffer.push_str(field);
+                    }
                 }
             }
 
-            buffer.push('\n');
+            rendered.push((id, buffer));
         }
 
-        print!("{buffer}");
+        rendered
     }
 
     pub fn resolutions(&self, resolutions: Vec<Resolution>) {
@@ -274,6 +390,191 @@ impl Display {
         }
     }
 
+    pub fn player_list(&self, players: &[(String, Duration)]) {
+        let mut buffer = String::new();
+        for (id, (title, elapsed)) in players.iter().enumerate() {
+            writeln!(
+                buffer,
+                "{}{}: {} {}",
+                id + 1,
+                " ".repeat(display_length(players.len()) - display_length(id + 1)),
+                self.theme.title.paint(title),
+                self.theme
+                    .duration
+                    .paint(pretty_duration(elapsed.as_secs())),
+            )
+            .expect("Formatting failed unexpectedly");
+        }
+        print!("{buffer}");
+    }
+
+    pub fn blocklists(&self, lists: &[(&str, Vec<&str>)]) {
+        let mut buffer = String::new();
+        for (title, entries) in lists {
+            writeln!(buffer, "# {title}").expect("Formatting failed unexpectedly");
+            if entries.is_empty() {
+                writeln!(buffer, "(none)").expect("Formatting failed unexpectedly");
+            }
+            for entry in entries {
+                writeln!(buffer, "{}", self.theme.error.paint(entry),)
+                    .expect("Formatting failed unexpectedly");
+            }
+            buffer.push('\n');
+        }
+        print!("{buffer}");
+    }
+
+    pub fn playlist_list(&self, playlists: &[(&str, usize)]) {
+        let mut buffer = String::new();
+        for (name, len) in playlists {
+            writeln!(
+                buffer,
+                "{} {}",
+                self.theme.title.paint(name),
+                self.theme.duration.paint(format_args!("({len} videos)")),
+            )
+            .expect("Formatting failed unexpectedly");
+        }
+        print!("{buffer}");
+    }
+
+    /// Numbered list of the reasons a report can give
+    pub fn report_reasons(&self, reasons: &[&str]) {
+        let mut buffer = String::new();
+        for (id, reason) in reasons.iter().enumerate() {
+            writeln!(buffer, "{}: {}", self.theme.title.paint(id + 1), reason,)
+                .expect("Formatting failed unexpectedly");
+        }
+        print!("{buffer}");
+    }
+
+    pub fn download_list(&self, jobs: &[JobInfo]) {
+        const BAR_WIDTH: usize = 20;
+
+        let title_width = jobs
+            .iter()
+            .map(|j| UnicodeWidthStr::width(j.title.as_str()))
+            .max()
+            .unwrap_or(0);
+
+        let mut buffer = String::new();
+        for (id, job) in jobs.iter().enumerate() {
+            write!(
+                buffer,
+                "{}{}: {}{} ",
+                id + 1,
+                " ".repeat(display_length(jobs.len()) - display_length(id + 1)),
+                self.theme.title.paint(&job.title),
+                " ".repeat(title_width - UnicodeWidthStr::width(job.title.as_str())),
+            )
+            .expect("Formatting failed unexpectedly");
+
+            match &job.status {
+                JobStatus::Queued => buffer.push_str("queued"),
+                JobStatus::Done => write!(
+                    buffer,
+                    "{} {}",
+                    self.theme.info.paint("done"),
+                    pretty_size(job.downloaded)
+                )
+                .expect("Formatting failed unexpectedly"),
+                JobStatus::Cancelled => {
+                    write!(buffer, "{}", self.theme.warning.paint("cancelled"),)
+                        .expect("Formatting failed unexpectedly")
+                }
+                JobStatus::Failed(err) => write!(
+                    buffer,
+                    "{}",
+                    self.theme.error.paint(format_args!("failed: {err}")),
+                )
+                .expect("Formatting failed unexpectedly"),
+                JobStatus::Running => {
+                    if let Some(total) = job.total.filter(|t| *t > 0) {
+                        let done = cmp::min(job.downloaded, total);
+                        let filled = (done as usize * BAR_WIDTH) / total as usize;
+                        write!(
+                            buffer,
+                            "{} {:>3}% {}/{}",
+                            self.theme.info.paint(format_args!(
+                                "[{}{}]",
+                                "#".repeat(filled),
+                                " ".repeat(BAR_WIDTH - filled)
+                            )),
+                            done * 100 / total,
+                            pretty_size(done),
+                            pretty_size(total),
+                        )
+                        .expect("Formatting failed unexpectedly");
+                    } else {
+                        write!(buffer, "{}", pretty_size(job.downloaded))
+                            .expect("Formatting failed unexpectedly");
+                    }
+                    if let Some(speed) = job.speed {
+                        write!(buffer, " {}/s", pretty_size(speed))
+                            .expect("Formatting failed unexpectedly");
+                    }
+                    if let Some(eta) = job.eta {
+                        write!(buffer, " ETA {}", pretty_duration(eta))
+                            .expect("Formatting failed unexpectedly");
+                    }
+                }
+            }
+            buffer.push('\n');
+        }
+
+        print!("{buffer}");
+    }
+
+    pub fn stats(&self, stats: &Stats) {
+        if stats.videos == 0 {
+            self.warn(&"No video in the history yet");
+            return;
+        }
+
+        let mut buffer = String::new();
+        self.stats_table(&mut buffer, "HOURS WATCHED PER WEEK", &stats.weeks);
+        self.stats_table(&mut buffer, "HOURS WATCHED PER MONTH", &stats.months);
+        self.stats_table(&mut buffer, "TOP CHANNELS", &stats.channels);
+        self.stats_table(&mut buffer, "TOP INSTANCES", &stats.instances);
+
+        writeln!(buffer, "Videos watched: {}", stats.videos)
+            .expect("Formatting failed unexpectedly");
+        if let Some(d) = stats.average_duration {
+            writeln!(buffer, "Average length: {}", pretty_duration(d))
+                .expect("Formatting failed unexpectedly");
+        }
+        if let Some(share) = stats.abandoned {
+            writeln!(buffer, "Abandoned early: {:.0}%", share * 100.)
+                .expect("Formatting failed unexpectedly");
+        }
+        print!("{buffer}");
+    }
+
+    fn stats_table(&self, buffer: &mut String, title: &str, rows: &[Row]) {
+        if rows.is_empty() {
+            return;
+        }
+
+        let label_width = rows
+            .iter()
+            .map(|r| UnicodeWidthStr::width(r.label.as_str()))
+            .max()
+            .unwrap_or(0);
+        writeln!(buffer, "# {title}").expect("Formatting failed unexpectedly");
+        for r in rows {
+            writeln!(
+                buffer,
+                "{}{} {:>6.1}h {:>4} video(s)",
+                self.theme.title.paint(&r.label),
+                " ".repeat(label_width - UnicodeWidthStr::width(r.label.as_str())),
+                r.seconds as f64 / 3600.,
+                r.videos,
+            )
+            .expect("Formatting failed unexpectedly");
+        }
+        buffer.push('\n');
+    }
+
     pub fn welcome(&self, instance: &str) {
         self.line('=');
         self.print_centered(&format!("Connecting to: {instance}"));
@@ -281,25 +582,11 @@ impl Display {
     }
 
     pub fn err<T: fmt::Display>(&self, err: &T) {
-        println!(
-            "{}{}{}{}{}",
-            fg_color(color::Red, self.colors),
-            style::Bold,
-            err,
-            style::Reset,
-            fg_color(color::Reset, self.colors)
-        );
+        println!("{}", self.theme.error.paint(err));
     }
 
     pub fn warn<T: fmt::Display>(&self, warn: &T) {
-        println!(
-            "{}{}{}{}{}",
-            fg_color(color::Yellow, self.colors),
-            style::Bold,
-            warn,
-            style::Reset,
-            fg_color(color::Reset, self.colors)
-        );
+        println!("{}", self.theme.warning.paint(warn));
     }
 
     pub fn message(&self, msg: &str) {
@@ -307,86 +594,176 @@ impl Display {
     }
 
     pub fn info(&self, msg: &str) {
-        println!("{}{}{}{}", style::Bold, style::Underline, msg, style::Reset);
+        println!("{}", self.theme.info.paint(msg));
     }
 
-    pub fn mode_info(&self, mode: &str, total: usize, offset: usize, current_len: usize) {
-        println!(
-            "{}{}{} results {} to {} out of {} (:h for help){}",
-            style::Bold,
-            style::Underline,
-            mode,
-            offset,
-            offset + current_len,
-            total,
-            style::Reset
-        );
+    pub fn render_mode_info(
+        &self,
+        mode: &str,
+        total: usize,
+        offset: usize,
+        current_len: usize,
+    ) -> String {
+        format!(
+            "{}\n",
+            self.theme.info.paint(format_args!(
+                "{} results {} to {} out of {} (:h for help)",
+                mode,
+                offset,
+                offset + current_len,
+                total,
+            ))
+        )
     }
 
     pub fn continue_despite_error(&self) {
         println!(
-            "{}{}You can continue browsing the PeerTube network{}",
-            style::Bold,
-            style::Underline,
-            style::Reset
+            "{}",
+            self.theme
+                .info
+                .paint("You can continue browsing the PeerTube network")
         );
     }
 
     pub fn video_info(&self, video: &Video) {
-        let cols = col_size();
-        self.line('=');
-        self.print_centered(video.name());
-        self.line('=');
-        if let Ok(Some(d)) = video.description() {
-            if !d.is_empty() {
-                self.print_centered("DESCRIPTION");
-                self.line('=');
-                println!("{}", fill(&d, cols));
-                self.line('=');
-            }
+        print!("{}", self.render_video_info(video, col_size()));
+    }
+
+    pub fn render_video_info(&self, video: &Video, cols: usize) -> String {
+        let description = video.description().ok().flatten();
+        self.render_video(video, Some(description), cols)
+    }
+
+    /// Same as `render_video_info` but doesn't wait for the description to be fetched
+    pub fn render_loaded_video_info(&self, video: &Video, cols: usize) -> String {
+        self.render_video(video, video.loaded_description(), cols)
+    }
+
+    /// `description` is `None` while it is being fetched
+    fn render_video(
+        &self,
+        video: &Video,
+        description: Option<Option<String>>,
+        cols: usize,
+    ) -> String {
+        let mut buffer = String::new();
+        let b = &mut buffer;
+        line(b, '=', cols);
+        centered(b, video.name(), cols);
+        line(b, '=', cols);
+        let description = match description {
+            None => Some("Loading…".to_owned()),
+            Some(d) => d.filter(|d| !d.is_empty()),
+        };
+        if let Some(d) = description {
+            centered(b, "DESCRIPTION", cols);
+            line(b, '=', cols);
+            writeln!(b, "{}", fill(&d, cols)).expect("Formatting failed unexpectedly");
+            line(b, '=', cols);
         }
-        self.print_centered("INFORMATION");
-        self.line('=');
-        println!(
-            "duration : {}",
-            pretty_duration_or_live(video.duration(), video.is_live())
-        );
-        println!("views    : {}", video.views());
-        println!("likes    : {}", video.likes());
-        println!("dislikes : {}", video.dislikes());
-        println!("released : {}", full_date(video.published()));
-        println!("account  : {}", video.account_display());
-        println!("channel  : {}", video.channel_display());
-        println!("host     : {}", video.host());
-        println!("url      : {}", video.watch_url());
+        centered(b, "INFORMATION", cols);
+        line(b, '=', cols);
+        writeln!(
+            b,
+            "duration : {}\n\
+             views    : {}\n\
+             likes    : {}\n\
+             dislikes : {}\n\
+             released : {}\n\
+             account  : {}\n\
+             channel  : {}\n\
+             host     : {}\n\
+             url      : {}",
+            pretty_duration_or_live(video.duration(), video.is_live()),
+            video.views(),
+            video.likes(),
+            video.dislikes(),
+            full_date(video.published()),
+            video.account_display(),
+            video.channel_display(),
+            video.host(),
+            video.watch_url(),
+        )
+        .expect("Formatting failed unexpectedly");
         if video.nsfw() {
-            println!("{}nsfw{}", fg_color(color::Red, self.colors), style::Reset,);
+            writeln!(b, "{}", self.theme.nsfw.paint("nsfw"))
+                .expect("Formatting failed unexpectedly");
         }
-        self.line('=');
+        line(b, '=', cols);
+        buffer
     }
 
     pub fn channel_info(&self, channel: &Channel) {
-        let cols = col_size();
-        self.line('=');
-        self.print_centered(channel.display_name());
-        self.line('=');
+        print!("{}", self.render_channel_info(channel, col_size()));
+    }
+
+    pub fn render_channel_info(&self, channel: &Channel, cols: usize) -> String {
+        let mut buffer = String::new();
+        let b = &mut buffer;
+        line(b, '=', cols);
+        centered(b, channel.display_name(), cols);
+        line(b, '=', cols);
         if let Some(d) = channel.description() {
             if !d.is_empty() {
-                self.print_centered("DESCRIPTION");
-                self.line('=');
-                println!("{}", fill(d, cols));
-                self.line('=');
+                centered(b, "DESCRIPTION", cols);
+                line(b, '=', cols);
+                writeln!(b, "{}", fill(d, cols)).expect("Formatting failed unexpectedly");
+                line(b, '=', cols);
             }
         }
-        println!("name          : {}", channel.name());
-        println!("display_name  : {}", channel.display_name());
-        println!("host          : {}", channel.host());
-        println!("followers     : {}", channel.followers());
-        println!("created       : {}", channel.created_at());
-        println!("handle        : {}", channel.handle());
-        println!("rss feed      : {}", channel.rss());
-        println!("atom feed     : {}", channel.atom());
-        self.line('=');
+        writeln!(
+            b,
+            "name          : {}\n\
+             display_name  : {}\n\
+             host          : {}\n\
+             followers     : {}\n\
+             created       : {}\n\
+             handle        : {}\n\
+             rss feed      : {}\n\
+             atom feed     : {}",
+            channel.name(),
+            channel.display_name(),
+            channel.host(),
+            channel.followers(),
+            channel.created_at(),
+            channel.handle(),
+            channel.rss(),
+            channel.atom(),
+        )
+        .expect("Formatting failed unexpectedly");
+        line(b, '=', cols);
+        buffer
+    }
+
+    pub fn render_comment_info(&self, comment: &Comment, cols: usize) -> String {
+        let mut buffer = String::new();
+        let b = &mut buffer;
+        line(b, '=', cols);
+        centered(
+            b,
+            &format!(
+                "{} ({})",
+                comment.author_display_name(),
+                comment.author_handle()
+            ),
+            cols,
+        );
+        line(b, '=', cols);
+        writeln!(b, "{}", fill(&remove_html(comment.content()), cols))
+            .expect("Formatting failed unexpectedly");
+        line(b, '=', cols);
+        writeln!(
+            b,
+            "date    : {}\n\
+             replies : {}\n\
+             url     : {}",
+            full_date(comment.created_at()),
+            comment.replies(),
+            comment.url(),
+        )
+        .expect("Formatting failed unexpectedly");
+        line(b, '=', cols);
+        buffer
     }
 
     pub fn report_error(&self, err: impl Debug, host: &str) {
@@ -407,24 +784,18 @@ impl Display {
     }
 
     fn line(&self, c: char) {
-        let cols = col_size();
-        let line_str = c.to_string().repeat(cols);
-        println!("{line_str}");
+        let mut buffer = String::new();
+        line(&mut buffer, c, col_size());
+        print!("{buffer}");
     }
 
     fn print_centered(&self, s: &str) {
-        let cols = col_size();
-        let len = s.chars().count();
-        if len > cols {
-            println!("{s}");
-            return;
-        }
-
-        let before = ' '.to_string().repeat((cols - len) / 2);
-        println!("{before}{s}");
+        let mut buffer = String::new();
+        centered(&mut buffer, s, col_size());
+        print!("{buffer}");
     }
 
-    pub fn help(&self) {
+    pub fn help(&self, commands: &[CustomCommand]) {
         println!(
             "\
             # MODES\n\n\
@@ -434,12 +805,62 @@ impl Display {
             :channels <keywords> : search for a channel\n\
             :info  <ID>          : get info for one of the currently displayed items\n\
             :comments <ID>       : get comments for a video\n\
-            :browser <ID>        : open an item in the browser\n\n\
+            :browser <ID>        : open an item in the browser\n\
+            :play <ID> [RES]     : play a video, optionally at a given resolution (ex: 480p)\n\
+            :play-with <P> <ID>  : play a video with the player profile P\n\n\
+            # DOWNLOADS\n\
+            :download <IDs>      : download videos in the background (ex: 1 3 5-8)\n\
+            :downloads           : show the progress of the downloads\n\
+            :cancel <ID>         : cancel a download\n\
+            :retry <ID>          : retry a failed or cancelled download\n\n\
+            # QUEUE\n\
+            :queue <IDs>         : add videos to the queue (ex: 1 3 5-8)\n\
+            :queue               : show the queue\n\
+            :play-queue          : play the videos of the queue\n\
+            :autoplay on|off     : play the following videos after the selected one\n\n\
+            # HISTORY\n\
+            :history [keywords]  : browse the videos already viewed\n\
+            :forget <ID>         : remove a video from the history\n\
+            :clear-history       : remove all the videos from the history\n\
+            :stats               : show statistics on the videos watched\n\
+            :private on|off      : stop saving the history and running the hooks\n\n\
+            # BLOCKING\n\
+            :block-instance <ID> : block the instance of an item\n\
+            :block-channel <ID>  : block the channel of a video\n\
+            :block-account <ID>  : block the account of a video or a comment\n\
+            :unblock <entry>     : unblock an instance, a handle or a keyword\n\
+            :blocklist           : show what is blocked\n\
+            :mute-instance <ID>  : mute the instance of an item on your account\n\
+            :mute-account <ID>   : mute the account of a video or a comment on your account\n\
+            :unmute <entry>      : unmute an instance or an account handle\n\
+            :mutes               : show what is muted on your account\n\
+            :report <ID> <R>     : report a video or a comment to the moderators for the reason R\n\n\
+            # BOOKMARKS AND PLAYLISTS\n\
+            :bookmark <ID>       : bookmark a video, or remove it from the bookmarks\n\
+            :bookmarks           : browse the bookmarks\n\
+            :playlist-add N <ID> : add a video to the local playlist N\n\
+            :playlist [N]        : browse the local playlist N, or list them\n\
+            :playlist-rm N [ID]  : remove a video from the playlist N, or the whole playlist\n\n\
+            # PLAYERS\n\
+            :players             : list the players running in the background\n\
+            :kill <ID>           : stop a player running in the background\n\n\
             # NAVIGATING\n\
             :n(ext)              : see more items\n\
             :p(revious)          : return to the previous items\n\
             :q(uit)\n\
         "
         );
+
+        if !commands.is_empty() {
+            println!("# CUSTOM COMMANDS");
+            for c in commands {
+                println!(
+                    "{:<20} : {}",
+                    format!(":{} <ID>", c.name),
+                    c.description.as_ref().unwrap_or(&c.command)
+                );
+            }
+            println!();
+        }
     }
 }
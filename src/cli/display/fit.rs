@@ -0,0 +1,317 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use super::helpers::truncate_width;
+use unicode_width::UnicodeWidthStr;
+
+use std::cmp::Reverse;
+
+/// How a field of a layout gives way when the lists are wider than the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fit {
+    /// Literal text of the template
+    Text,
+    /// Always displayed whole
+    Keep,
+    /// Shortened with an ellipsis, the lowest priorities first
+    Truncate(u8),
+    /// Hidden with its column, the lowest priorities first
+    Optional(u8),
+    /// Wrapped to the width of the terminal
+    Wrap,
+}
+
+/// Truncated columns are first shortened to this width, before hiding the optional ones
+const COMFORTABLE_WIDTH: usize = 20;
+/// Truncated columns are never shortened below this width
+const MIN_WIDTH: usize = 8;
+
+/// Fields of a row between two alignment points, with how they fit
+pub type Segment = Vec<(String, Fit)>;
+
+/// What the text between two alignment points can give way, for all the layouts of a list
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    /// Highest priority of its truncated fields, if it has some
+    truncate: Option<u8>,
+    /// Highest priority of its fields, set when they are all optional
+    optional: Option<u8>,
+}
+
+/// Combine the columns of the layouts, given as the fits of the fields of each column
+///
+/// A column is only hidden if it is optional in all the layouts
+pub fn columns(layouts: &[Vec<Vec<Fit>>]) -> Vec<Column> {
+    let len = layouts.iter().map(Vec::len).max().unwrap_or(0);
+    (0..len)
+        .map(|k| {
+            let fits = || {
+                layouts
+                    .iter()
+                    .filter_map(move |l| l.get(k))
+                    .flatten()
+                    .filter(|f| **f != Fit::Text)
+            };
+            let optional = fits()
+                .map(|f| match f {
+                    Fit::Optional(p) => Some(*p),
+                    _ => None,
+                })
+                .try_fold(None, |max: Option<u8>, p| Some(max.max(Some(p?))))
+                .flatten();
+            let truncate = fits()
+                .filter_map(|f| match f {
+                    Fit::Truncate(p) => Some(*p),
+                    _ => None,
+                })
+                .max();
+            Column { truncate, optional }
+        })
+        .collect()
+}
+
+/// Width of the first line of a segment
+pub fn width(segment: &Segment) -> usize {
+    let mut w = 0;
+    for (s, _) in segment {
+        match s.split_once('\n') {
+            Some((first, _)) => return w + UnicodeWidthStr::width(first),
+            None => w += UnicodeWidthStr::width(s.as_str()),
+        }
+    }
+    w
+}
+
+/// Width of the first line of a segment once its truncated fields are emptied
+fn fixed_width(segment: &Segment) -> usize {
+    let fixed: Segment = segment
+        .iter()
+        .map(|(s, f)| match f {
+            Fit::Truncate(_) => (String::new(), *f),
+            _ => (s.clone(), *f),
+        })
+        .collect();
+    width(&fixed)
+}
+
+/// Fit the rows in `cols` columns of terminal, `prefix` being the width before the rows
+///
+/// Truncated fields are shortened, then optional columns are hidden, then truncated fields are shortened further.
+/// Returns the widths of the columns, `None` for hidden ones, and shortens the fields of `rows` to fit in them.
+/// `None` rows (the blocked items) are ignored.
+pub fn fit(
+    rows: &mut [Option<Vec<Segment>>],
+    columns: &[Column],
+    prefix: usize,
+    cols: usize,
+) -> Vec<Option<usize>> {
+    let mut widths = vec![0; columns.len()];
+    // Columns can't be narrower than the fields that aren't truncated
+    let mut fixed = vec![0; columns.len()];
+    for row in rows.iter().flatten() {
+        for (k, segment) in row.iter().enumerate() {
+            widths[k] = widths[k].max(width(segment));
+            fixed[k] = fixed[k].max(fixed_width(segment));
+        }
+    }
+    let mut hidden = vec![false; columns.len()];
+
+    let total = |widths: &[usize], hidden: &[bool]| {
+        prefix
+            + rows
+                .iter()
+                .flatten()
+                .map(|row| {
+                    let last = row.len() - 1;
+                    row.iter()
+                        .enumerate()
+                        .filter(|(k, _)| !hidden[*k])
+                        .map(|(k, segment)| {
+                            if k == last {
+                                width(segment).min(widths[k])
+                            } else {
+                                widths[k]
+                            }
+                        })
+                        .sum::<usize>()
+                })
+                .max()
+                .unwrap_or(0)
+    };
+
+    let shrink = |widths: &mut Vec<usize>, hidden: &[bool], floor: usize| loop {
+        let over = total(widths, hidden).saturating_sub(cols);
+        if over == 0 {
+            return;
+        }
+        let candidate = (0..columns.len())
+            .filter(|k| !hidden[*k] && widths[*k] > floor.max(fixed[*k]))
+            .filter_map(|k| Some((columns[k].truncate?, Reverse(widths[k]), k)))
+            .min();
+        match candidate {
+            Some((_, _, k)) => widths[k] -= over.min(widths[k] - floor.max(fixed[k])),
+            None => return,
+        }
+    };
+
+    shrink(&mut widths, &hidden, COMFORTABLE_WIDTH);
+    while total(&widths, &hidden) > cols {
+        let candidate = (0..columns.len())
+            .filter(|k| !hidden[*k])
+            .filter_map(|k| Some((columns[k].optional?, Reverse(k))))
+            .min();
+        match candidate {
+            Some((_, Reverse(k))) => hidden[k] = true,
+            None => break,
+        }
+    }
+    shrink(&mut widths, &hidden, MIN_WIDTH);
+
+    for row in rows.iter_mut().flatten() {
+        for (k, segment) in row.iter_mut().enumerate() {
+            if !hidden[k] {
+                truncate(segment, widths[k]);
+            }
+        }
+    }
+
+    widths
+        .into_iter()
+        .zip(hidden)
+        .map(|(w, h)| (!h).then_some(w))
+        .collect()
+}
+
+/// Shorten the truncated fields of a segment, the lowest priorities first, until it fits in `max`
+fn truncate(segment: &mut Segment, max: usize) {
+    let mut excess = width(segment).saturating_sub(max);
+    let mut truncated: Vec<_> = segment
+        .iter_mut()
+        .filter_map(|(s, f)| match f {
+            Fit::Truncate(p) => Some((*p, s)),
+            _ => None,
+        })
+        .collect();
+    truncated.sort_by_key(|(p, _)| *p);
+    for (_, s) in truncated {
+        if excess == 0 {
+            return;
+        }
+        let w = UnicodeWidthStr::width(s.as_str());
+        *s = truncate_width(s, w.saturating_sub(excess));
+        excess = excess.saturating_sub(w - UnicodeWidthStr::width(s.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn row(segments: &[&[(&str, Fit)]]) -> Option<Vec<Segment>> {
+        Some(
+            segments
+                .iter()
+                .map(|s| s.iter().map(|(t, f)| (t.to_string(), *f)).collect())
+                .collect(),
+        )
+    }
+
+    fn texts(row: &Option<Vec<Segment>>) -> Vec<String> {
+        row.iter()
+            .flatten()
+            .map(|s| s.iter().map(|(t, _)| t.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn fitting() {
+        use Fit::*;
+        let layout = vec![
+            vec![Truncate(2), Text],
+            vec![Truncate(1)],
+            vec![Text, Keep, Text],
+            vec![Optional(1), Text],
+            vec![Optional(2), Text],
+            vec![Keep],
+        ];
+        let columns = columns(&[layout]);
+        let original = vec![
+            row(&[
+                &[
+                    ("A very long title for a video, too long", Truncate(2)),
+                    (" ", Text),
+                ],
+                &[("A channel with a long name", Truncate(1))],
+                &[(" [", Text), ("1h20", Keep), ("] ", Text)],
+                &[("12K", Optional(1)), (" ", Text)],
+                &[("3 months ago", Optional(2)), (" ", Text)],
+                &[("nsfw", Keep)],
+            ]),
+            None,
+        ];
+
+        let mut rows = original.clone();
+        assert_eq!(
+            fit(&mut rows, &columns, 4, 200),
+            vec![Some(40), Some(26), Some(8), Some(4), Some(13), Some(4)]
+        );
+        assert_eq!(rows, original);
+
+        // The channel is shortened before the title
+        let mut rows = original.clone();
+        fit(&mut rows, &columns, 4, 93);
+        assert_eq!(
+            texts(&rows[0]),
+            vec![
+                "A very long title for a video, too long ",
+                "A channel with a lo…",
+                " [1h20] ",
+                "12K ",
+                "3 months ago ",
+                "nsfw"
+            ]
+        );
+
+        // The views are hidden once the title and channel are shortened to a comfortable width
+        let mut rows = original.clone();
+        assert_eq!(
+            fit(&mut rows, &columns, 4, 70),
+            vec![Some(20), Some(20), Some(8), None, Some(13), Some(4)]
+        );
+        assert_eq!(
+            texts(&rows[0])[..2],
+            ["A very long title … ", "A channel with a lo…"]
+        );
+
+        let mut rows = original;
+        assert_eq!(
+            fit(&mut rows, &columns, 4, 30),
+            vec![Some(8), Some(8), Some(8), None, None, Some(4)]
+        );
+    }
+
+    #[test]
+    fn fixed_fields() {
+        use Fit::*;
+        // `{name} [{duration}] |{channel}`, the duration is wider than the minimum width
+        let layout = vec![vec![Truncate(2), Text, Keep, Text], vec![Truncate(1)]];
+        let columns = columns(&[layout]);
+        let mut rows = vec![row(&[
+            &[
+                ("Some video title", Truncate(2)),
+                (" [", Text),
+                ("1:20:00", Keep),
+                ("] ", Text),
+            ],
+            &[("A channel", Truncate(1))],
+        ])];
+        assert_eq!(fit(&mut rows, &columns, 4, 16), vec![Some(11), Some(8)]);
+        assert_eq!(texts(&rows[0]), vec![" [1:20:00] ", "A chann…"]);
+    }
+}
@@ -7,6 +7,7 @@
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
 use time::{format_description, Duration, OffsetDateTime};
+use unicode_width::UnicodeWidthChar;
 
 pub fn pretty_size(mut s: u64) -> String {
     const PREFIXES: [&str; 5] = ["", "K", "M", "G", "E"];
@@ -36,6 +37,36 @@ pub fn pretty_date(d: OffsetDateTime) -> String {
     pretty_duration_since(now - d)
 }
 
+/// Number of days since the date (ex: `12d`)
+pub fn age_in_days(d: OffsetDateTime) -> String {
+    format!("{}d", (OffsetDateTime::now_utc() - d).whole_days().max(0))
+}
+
+/// Share of likes among the ratings, empty when there are none
+pub fn like_ratio(likes: u64, dislikes: u64) -> String {
+    match likes + dislikes {
+        0 => String::new(),
+        total => format!("{}%", likes * 100 / total),
+    }
+}
+
+/// First line of a text, shortened to `EXCERPT_LENGTH` characters
+pub fn excerpt(text: &str) -> String {
+    const EXCERPT_LENGTH: usize = 40;
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("");
+    if line.chars().count() > EXCERPT_LENGTH {
+        let mut s: String = line.chars().take(EXCERPT_LENGTH - 1).collect();
+        s.push('…');
+        s
+    } else {
+        line.to_owned()
+    }
+}
+
 pub fn pretty_duration_since(d: Duration) -> String {
     if d.whole_milliseconds() < 0 {
         return "From the future. Bug?".to_string();
@@ -169,6 +200,29 @@ pub fn remove_html(mut input: &str) -> String {
     s
 }
 
+/// Shorten a text to `width` columns, ending it with `…` when it is cut
+pub fn truncate_width(text: &str, width: usize) -> String {
+    if unicode_width::UnicodeWidthStr::width(text) <= width {
+        return text.to_owned();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut s = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width - 1 {
+            break;
+        }
+        used += w;
+        s.push(c);
+    }
+    s.push('…');
+    s
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,6 +243,21 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn excerpts() {
+        assert_eq!(excerpt("\n  Short line \nSecond line"), "Short line");
+        assert_eq!(
+            excerpt("A description whose first line is too long to be shown whole"),
+            "A description whose first line is too l…"
+        );
+        assert_eq!(truncate_width("Short", 5), "Short");
+        assert_eq!(truncate_width("A longer title", 8), "A longe…");
+        assert_eq!(truncate_width("日本語のタイトル", 6), "日本…");
+        assert_eq!(truncate_width("Title", 0), "");
+        assert_eq!(like_ratio(0, 0), "");
+        assert_eq!(like_ratio(3, 1), "75%");
+    }
+
     #[test]
     pub fn length() {
         assert_eq!(display_length(0), 1);
@@ -6,15 +6,27 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use peertube_api::{channels::Channel, Comment, Video};
+use peertube_api::{channels::Channel, Comment, Video, VideoState};
 use std::fmt;
-use termion::{color, style};
+use termion::style;
 
+use super::fit::Fit;
 use super::helpers::*;
+use super::theme::Theme;
 
-pub trait InnerLayoutItem {
+pub trait InnerLayoutItem: Sized {
     type Data;
-    fn display(&self, data: &Self::Data) -> String;
+    /// `seen` is set when the item is in the history
+    fn display(&self, data: &Self::Data, seen: bool) -> String;
+
+    /// Field of a template from its name
+    fn from_name(name: &str) -> Option<Self>;
+
+    /// Literal text of a template
+    fn text(s: String) -> Self;
+
+    /// How the field gives way on narrow terminals
+    fn fit(&self) -> Fit;
 }
 
 pub enum LayoutItem<I: InnerLayoutItem> {
@@ -26,11 +38,11 @@ impl<D, I> LayoutItem<I>
 where
     I: InnerLayoutItem<Data = D>,
 {
-    pub fn display(&self, data: &I::Data) -> String {
+    pub fn display(&self, data: &I::Data, seen: bool) -> String {
         match self {
             LayoutItem::Style(_) => panic!("Internal Error: cannot display style here"),
             LayoutItem::Alignment => panic!("Internal error, trying to display an alignment"),
-            LayoutItem::Inner(i) => i.display(data),
+            LayoutItem::Inner(i) => i.display(data, seen),
         }
     }
 
@@ -42,6 +54,13 @@ where
         }
     }
 
+    pub fn fit(&self) -> Fit {
+        match self {
+            LayoutItem::Inner(i) => i.fit(),
+            _ => panic!("Internal error: fit of a style or an alignment"),
+        }
+    }
+
     pub fn is_align(&self) -> bool {
         matches!(self, LayoutItem::Alignment)
     }
@@ -51,7 +70,6 @@ where
     }
 }
 
-#[allow(unused)]
 pub enum VideoLayoutItem {
     Name,
     Channel,
@@ -60,13 +78,28 @@ pub enum VideoLayoutItem {
     Views,
     Duration,
     Published,
+    Likes,
+    Dislikes,
+    /// Share of likes among the ratings
+    Ratio,
+    Account,
+    Language,
+    Category,
+    Licence,
+    /// Badge for lives and videos not published yet
+    State,
+    Seen,
+    /// First line of the description
+    Excerpt,
+    /// Days since the publication
+    Age,
     String(String),
 }
 
 impl InnerLayoutItem for VideoLayoutItem {
     type Data = Video;
 
-    fn display(&self, v: &Self::Data) -> String {
+    fn display(&self, v: &Self::Data, seen: bool) -> String {
         match self {
             VideoLayoutItem::Name => v.name().to_owned(),
             VideoLayoutItem::Channel => v.channel_display().to_owned(),
@@ -81,137 +114,333 @@ impl InnerLayoutItem for VideoLayoutItem {
             VideoLayoutItem::Views => display_count(v.views()),
             VideoLayoutItem::Duration => pretty_duration_or_live(v.duration(), v.is_live()),
             VideoLayoutItem::Published => pretty_date(v.published()),
+            VideoLayoutItem::Likes => display_count(v.likes()),
+            VideoLayoutItem::Dislikes => display_count(v.dislikes()),
+            VideoLayoutItem::Ratio => like_ratio(v.likes(), v.dislikes()),
+            VideoLayoutItem::Account => v.account_display().to_owned(),
+            VideoLayoutItem::Language => v.language().unwrap_or("").to_owned(),
+            VideoLayoutItem::Category => v.category().unwrap_or("").to_owned(),
+            VideoLayoutItem::Licence => v.licence().unwrap_or("").to_owned(),
+            VideoLayoutItem::State => match v.state() {
+                _ if v.is_live() => "LIVE",
+                VideoState::WaitingForLive => "upcoming",
+                VideoState::LiveEnded => "ended",
+                VideoState::ToTranscode => "transcoding",
+                VideoState::ToImport => "importing",
+                _ => "",
+            }
+            .to_owned(),
+            VideoLayoutItem::Seen => if seen { "✓" } else { "" }.to_owned(),
+            VideoLayoutItem::Excerpt => excerpt(v.short_desc().unwrap_or("")),
+            VideoLayoutItem::Age => age_in_days(v.published()),
             VideoLayoutItem::String(s) => s.clone(),
         }
     }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "name" => VideoLayoutItem::Name,
+            "channel" => VideoLayoutItem::Channel,
+            "host" => VideoLayoutItem::Host,
+            "nsfw" => VideoLayoutItem::Nsfw,
+            "views" => VideoLayoutItem::Views,
+            "duration" => VideoLayoutItem::Duration,
+            "published" => VideoLayoutItem::Published,
+            "likes" => VideoLayoutItem::Likes,
+            "dislikes" => VideoLayoutItem::Dislikes,
+            "ratio" => VideoLayoutItem::Ratio,
+            "account" => VideoLayoutItem::Account,
+            "language" => VideoLayoutItem::Language,
+            "category" => VideoLayoutItem::Category,
+            "licence" => VideoLayoutItem::Licence,
+            "state" => VideoLayoutItem::State,
+            "seen" => VideoLayoutItem::Seen,
+            "excerpt" => VideoLayoutItem::Excerpt,
+            "age" => VideoLayoutItem::Age,
+            _ => return None,
+        })
+    }
+
+    fn text(s: String) -> Self {
+        VideoLayoutItem::String(s)
+    }
+
+    fn fit(&self) -> Fit {
+        match self {
+            VideoLayoutItem::Name => Fit::Truncate(2),
+            VideoLayoutItem::Channel | VideoLayoutItem::Account | VideoLayoutItem::Excerpt => {
+                Fit::Truncate(1)
+            }
+            VideoLayoutItem::Nsfw
+            | VideoLayoutItem::Duration
+            | VideoLayoutItem::State
+            | VideoLayoutItem::Seen => Fit::Keep,
+            VideoLayoutItem::Published | VideoLayoutItem::Age => Fit::Optional(2),
+            VideoLayoutItem::Views | VideoLayoutItem::Host => Fit::Optional(1),
+            VideoLayoutItem::Likes
+            | VideoLayoutItem::Dislikes
+            | VideoLayoutItem::Ratio
+            | VideoLayoutItem::Language
+            | VideoLayoutItem::Category
+            | VideoLayoutItem::Licence => Fit::Optional(0),
+            VideoLayoutItem::String(_) => Fit::Text,
+        }
+    }
 }
 
-#[allow(unused)]
 pub enum ChannelLayoutItem {
     Name,
     Host,
     Followers,
+    Handle,
+    Created,
+    /// First line of the description
+    Excerpt,
     String(String),
 }
 
 impl InnerLayoutItem for ChannelLayoutItem {
     type Data = Channel;
 
-    fn display(&self, c: &Self::Data) -> String {
+    fn display(&self, c: &Self::Data, _: bool) -> String {
         match self {
             ChannelLayoutItem::Name => c.display_name().to_owned(),
             ChannelLayoutItem::Host => c.host().to_owned(),
             ChannelLayoutItem::Followers => display_count(c.followers()),
+            ChannelLayoutItem::Handle => c.handle(),
+            ChannelLayoutItem::Created => pretty_date(c.created_at()),
+            ChannelLayoutItem::Excerpt => excerpt(c.description().as_deref().unwrap_or("")),
             ChannelLayoutItem::String(s) => s.clone(),
         }
     }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "name" => ChannelLayoutItem::Name,
+            "host" => ChannelLayoutItem::Host,
+            "followers" => ChannelLayoutItem::Followers,
+            "handle" => ChannelLayoutItem::Handle,
+            "created" => ChannelLayoutItem::Created,
+            "excerpt" => ChannelLayoutItem::Excerpt,
+            _ => return None,
+        })
+    }
+
+    fn text(s: String) -> Self {
+        ChannelLayoutItem::String(s)
+    }
+
+    fn fit(&self) -> Fit {
+        match self {
+            ChannelLayoutItem::Name => Fit::Truncate(2),
+            ChannelLayoutItem::Handle | ChannelLayoutItem::Excerpt => Fit::Truncate(1),
+            ChannelLayoutItem::Followers => Fit::Optional(2),
+            ChannelLayoutItem::Host | ChannelLayoutItem::Created => Fit::Optional(1),
+            ChannelLayoutItem::String(_) => Fit::Text,
+        }
+    }
 }
 
-#[allow(unused)]
 pub enum CommentLayoutItem {
     Content,
     Date,
     Author,
     Host,
+    Handle,
+    Replies,
     String(String),
 }
 
 impl InnerLayoutItem for CommentLayoutItem {
     type Data = Comment;
 
-    fn display(&self, c: &Self::Data) -> String {
+    fn display(&self, c: &Self::Data, _: bool) -> String {
         match self {
             CommentLayoutItem::Author => c.author_display_name().to_owned(),
             CommentLayoutItem::Host => c.author_host().to_owned(),
             CommentLayoutItem::Date => pretty_date(c.created_at()),
             CommentLayoutItem::Content => remove_html(c.content()),
+            CommentLayoutItem::Handle => c.author_handle(),
+            CommentLayoutItem::Replies => display_count(c.replies()),
             CommentLayoutItem::String(s) => s.clone(),
         }
     }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "content" => CommentLayoutItem::Content,
+            "date" => CommentLayoutItem::Date,
+            "author" => CommentLayoutItem::Author,
+            "host" => CommentLayoutItem::Host,
+            "handle" => CommentLayoutItem::Handle,
+            "replies" => CommentLayoutItem::Replies,
+            _ => return None,
+        })
+    }
+
+    fn text(s: String) -> Self {
+        CommentLayoutItem::String(s)
+    }
+
+    fn fit(&self) -> Fit {
+        match self {
+            CommentLayoutItem::Content => Fit::Wrap,
+            CommentLayoutItem::Author => Fit::Truncate(2),
+            CommentLayoutItem::Handle => Fit::Truncate(1),
+            CommentLayoutItem::Date => Fit::Optional(2),
+            CommentLayoutItem::Host | CommentLayoutItem::Replies => Fit::Optional(1),
+            CommentLayoutItem::String(_) => Fit::Text,
+        }
+    }
 }
 
-pub fn default_video_layouts() -> (
-    Vec<LayoutItem<VideoLayoutItem>>,
-    Vec<LayoutItem<VideoLayoutItem>>,
-) {
-    let video_layout = vec![
-        LayoutItem::Style(Box::new(color::Fg(color::Blue))),
-        LayoutItem::Inner(VideoLayoutItem::Name),
-        LayoutItem::Inner(VideoLayoutItem::String(" ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Green))),
-        LayoutItem::Inner(VideoLayoutItem::Channel),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Yellow))),
-        LayoutItem::Inner(VideoLayoutItem::String(" [".to_string())),
-        LayoutItem::Inner(VideoLayoutItem::Duration),
-        LayoutItem::Inner(VideoLayoutItem::String("] ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Green))),
-        LayoutItem::Inner(VideoLayoutItem::Views),
-        LayoutItem::Inner(VideoLayoutItem::String(" ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Inner(VideoLayoutItem::Published),
-        LayoutItem::Inner(VideoLayoutItem::String(" ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Red))),
-        LayoutItem::Inner(VideoLayoutItem::Nsfw),
-        LayoutItem::Style(Box::new(color::Fg(color::Reset))),
-    ];
-
-    let seen_video_layout = vec![
-        LayoutItem::Style(Box::new(style::Bold)),
-        LayoutItem::Inner(VideoLayoutItem::Name),
-        LayoutItem::Style(Box::new(style::Reset)),
-        LayoutItem::Inner(VideoLayoutItem::String(" ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Green))),
-        LayoutItem::Inner(VideoLayoutItem::Channel),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Yellow))),
-        LayoutItem::Inner(VideoLayoutItem::String(" [".to_string())),
-        LayoutItem::Inner(VideoLayoutItem::Duration),
-        LayoutItem::Inner(VideoLayoutItem::String("] ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Green))),
-        LayoutItem::Inner(VideoLayoutItem::Views),
-        LayoutItem::Inner(VideoLayoutItem::String(" ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Inner(VideoLayoutItem::Published),
-        LayoutItem::Inner(VideoLayoutItem::String(" ".to_string())),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Red))),
-        LayoutItem::Inner(VideoLayoutItem::Nsfw),
-        LayoutItem::Style(Box::new(color::Fg(color::Reset))),
-    ];
-
-    (video_layout, seen_video_layout)
+pub const DEFAULT_VIDEO_LAYOUT: &str = "{:title}{name}{:reset} |{:channel}{channel}{:reset}|{:duration} [{duration}]{:reset} |{:views}{views}{:reset} |{:date}{published}{:reset} |{:nsfw}{nsfw}{:reset}";
+pub const DEFAULT_SEEN_VIDEO_LAYOUT: &str = "{:bold}{name}{:reset} |{:channel}{channel}{:reset}|{:duration} [{duration}]{:reset} |{:views}{views}{:reset} |{:date}{published}{:reset} |{:nsfw}{nsfw}{:reset}";
+pub const DEFAULT_CHANNEL_LAYOUT: &str = "{:title}{name}{:reset}| {:views}{followers}{:reset}";
+pub const DEFAULT_COMMENT_LAYOUT: &str =
+    "{:title}{author}{:reset}| {:date}{date}{:reset}|\n{content}\n";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    UnknownField(String),
+    UnknownStyle(String),
+    /// A `{` isn't closed
+    Unclosed,
+    /// A `}` doesn't close anything
+    UnexpectedBrace,
 }
 
-pub fn default_channel_layouts() -> Vec<LayoutItem<ChannelLayoutItem>> {
-    vec![
-        LayoutItem::Style(Box::new(color::Fg(color::Blue))),
-        LayoutItem::Inner(ChannelLayoutItem::Name),
-        LayoutItem::Alignment,
-        LayoutItem::Inner(ChannelLayoutItem::String(" ".to_string())),
-        LayoutItem::Style(Box::new(color::Fg(color::Green))),
-        LayoutItem::Inner(ChannelLayoutItem::Followers),
-        LayoutItem::Style(Box::new(color::Fg(color::Reset))),
-    ]
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::UnknownField(name) => write!(f, "unknown field \"{name}\""),
+            LayoutError::UnknownStyle(name) => write!(f, "unknown style \"{name}\""),
+            LayoutError::Unclosed => write!(f, "a \"{{\" isn't closed"),
+            LayoutError::UnexpectedBrace => {
+                write!(f, "unexpected \"}}\", use \"\\}}\" to write one")
+            }
+        }
+    }
 }
 
-pub fn default_comment_layouts() -> Vec<LayoutItem<CommentLayoutItem>> {
-    vec![
-        LayoutItem::Style(Box::new(color::Fg(color::Blue))),
-        LayoutItem::Inner(CommentLayoutItem::Author),
-        LayoutItem::Alignment,
-        LayoutItem::Inner(CommentLayoutItem::String(" ".to_string())),
-        LayoutItem::Style(Box::new(color::Fg(color::Cyan))),
-        LayoutItem::Inner(CommentLayoutItem::Date),
-        LayoutItem::Alignment,
-        LayoutItem::Style(Box::new(color::Fg(color::Reset))),
-        LayoutItem::Inner(CommentLayoutItem::String("\n".to_string())),
-        LayoutItem::Inner(CommentLayoutItem::Content),
-        LayoutItem::Inner(CommentLayoutItem::String("\n".to_string())),
-    ]
+/// Parse a layout template, such as `{name:blue} |{channel:green} |{views}`
+///
+/// - `|` marks an alignment point
+/// - `{field}` is replaced by the field, `{field:style}` displays it with a style, reset after it
+/// - `{:style}` applies a style to what follows, styles can be combined with `+` (ex: `{:bold+red}`)
+/// - the roles of the theme can be used as styles (ex: `{name:title}`)
+/// - `\` writes the next character as is
+pub fn parse_layout<I: InnerLayoutItem>(
+    template: &str,
+    theme: &Theme,
+) -> Result<Vec<LayoutItem<I>>, LayoutError> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars();
+    let flush = |text: &mut String, items: &mut Vec<LayoutItem<I>>| {
+        if !text.is_empty() {
+            items.push(LayoutItem::Inner(I::text(std::mem::take(text))));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            '|' => {
+                flush(&mut text, &mut items);
+                items.push(LayoutItem::Alignment);
+            }
+            '}' => return Err(LayoutError::UnexpectedBrace),
+            '{' => {
+                flush(&mut text, &mut items);
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inner.push(c),
+                        None => return Err(LayoutError::Unclosed),
+                    }
+                }
+                let (field, styles) = inner.split_once(':').unwrap_or((&inner, ""));
+                for style in styles.split('+').filter(|s| !s.is_empty()) {
+                    let parsed = theme
+                        .style(style.trim())
+                        .ok_or_else(|| LayoutError::UnknownStyle(style.trim().to_owned()))?;
+                    items.push(LayoutItem::Style(Box::new(parsed)));
+                }
+                let field = field.trim();
+                if !field.is_empty() {
+                    items.push(LayoutItem::Inner(
+                        I::from_name(field)
+                            .ok_or_else(|| LayoutError::UnknownField(field.to_owned()))?,
+                    ));
+                    if !styles.is_empty() {
+                        items.push(LayoutItem::Style(Box::new(style::Reset)));
+                    }
+                }
+            }
+            c => text.push(c),
+        }
+    }
+    flush(&mut text, &mut items);
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use termion::color;
+
+    /// Short description of the items, with `[style]`, `|` and `<text>`
+    fn describe(items: &[LayoutItem<ChannelLayoutItem>]) -> String {
+        items
+            .iter()
+            .map(|item| match item {
+                LayoutItem::Style(s) => format!("[{:?}]", s.to_string()),
+                LayoutItem::Alignment => "|".to_owned(),
+                LayoutItem::Inner(ChannelLayoutItem::String(s)) => format!("<{s}>"),
+                LayoutItem::Inner(ChannelLayoutItem::Name) => "name".to_owned(),
+                LayoutItem::Inner(ChannelLayoutItem::Host) => "host".to_owned(),
+                LayoutItem::Inner(ChannelLayoutItem::Followers) => "followers".to_owned(),
+                LayoutItem::Inner(_) => "other".to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn templates() {
+        let theme = Theme::default();
+        assert_eq!(
+            describe(&parse_layout("{name:blue} |\\|{:bold+views}{host}", &theme).unwrap()),
+            format!(
+                "[{:?}]name[{:?}]< >|<|>[{:?}][{:?}]host",
+                color::Fg(color::Blue).to_string(),
+                style::Reset.to_string(),
+                style::Bold.to_string(),
+                color::Fg(color::Green).to_string()
+            )
+        );
+        assert!(matches!(
+            parse_layout::<ChannelLayoutItem>("{name}{views}", &theme),
+            Err(LayoutError::UnknownField(f)) if f == "views"
+        ));
+        assert!(matches!(
+            parse_layout::<ChannelLayoutItem>("{name:pink}", &theme),
+            Err(LayoutError::UnknownStyle(s)) if s == "pink"
+        ));
+        assert!(matches!(
+            parse_layout::<ChannelLayoutItem>("{name", &theme),
+            Err(LayoutError::Unclosed)
+        ));
+        assert!(matches!(
+            parse_layout::<ChannelLayoutItem>("name}", &theme),
+            Err(LayoutError::UnexpectedBrace)
+        ));
+
+        assert!(parse_layout::<VideoLayoutItem>(DEFAULT_VIDEO_LAYOUT, &theme).is_ok());
+        assert!(parse_layout::<VideoLayoutItem>(DEFAULT_SEEN_VIDEO_LAYOUT, &theme).is_ok());
+        assert!(parse_layout::<ChannelLayoutItem>(DEFAULT_CHANNEL_LAYOUT, &theme).is_ok());
+        assert!(parse_layout::<CommentLayoutItem>(DEFAULT_COMMENT_LAYOUT, &theme).is_ok());
+    }
 }
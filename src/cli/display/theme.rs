@@ -0,0 +1,284 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+use termion::{color, style};
+
+/// Roles that can be styled in the `[theme]` section
+pub const ROLES: [&str; 11] = [
+    "title", "channel", "duration", "views", "date", "nsfw", "error", "warning", "info", "prompt",
+    "hint",
+];
+
+/// The first one is the default
+pub const PRESETS: [&str; 3] = ["dark", "light", "high-contrast"];
+
+/// Escape sequence of a style, made of parts separated by `+` (ex: `bold+#268bd2`)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Style(String);
+
+impl Style {
+    pub fn parse(spec: &str) -> Option<Style> {
+        let mut s = String::new();
+        for part in spec.split('+').map(str::trim).filter(|p| !p.is_empty()) {
+            s.push_str(&basic_style(part)?);
+        }
+        Some(Style(s))
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Style {
+    /// Display `content` with this style, resetting it afterwards
+    pub fn paint<T: fmt::Display>(&self, content: T) -> Painted<'_, T> {
+        Painted(self, content)
+    }
+}
+
+pub struct Painted<'s, T>(&'s Style, T);
+
+impl<T: fmt::Display> fmt::Display for Painted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 .0.is_empty() {
+            write!(f, "{}", self.1)
+        } else {
+            write!(f, "{}{}{}", self.0, self.1, style::Reset)
+        }
+    }
+}
+
+/// A text attribute, a named color, a 256-color value (ex: `33`) or a truecolor one (ex: `#268bd2`)
+fn basic_style(name: &str) -> Option<String> {
+    Some(match name {
+        "reset" => style::Reset.to_string(),
+        "bold" => style::Bold.to_string(),
+        "faint" => style::Faint.to_string(),
+        "italic" => style::Italic.to_string(),
+        "underline" => style::Underline.to_string(),
+        "default" => color::Fg(color::Reset).to_string(),
+        "black" => color::Fg(color::Black).to_string(),
+        "red" => color::Fg(color::Red).to_string(),
+        "green" => color::Fg(color::Green).to_string(),
+        "yellow" => color::Fg(color::Yellow).to_string(),
+        "blue" => color::Fg(color::Blue).to_string(),
+        "magenta" => color::Fg(color::Magenta).to_string(),
+        "cyan" => color::Fg(color::Cyan).to_string(),
+        "white" => color::Fg(color::White).to_string(),
+        "light-red" => color::Fg(color::LightRed).to_string(),
+        "light-green" => color::Fg(color::LightGreen).to_string(),
+        "light-yellow" => color::Fg(color::LightYellow).to_string(),
+        "light-blue" => color::Fg(color::LightBlue).to_string(),
+        "light-magenta" => color::Fg(color::LightMagenta).to_string(),
+        "light-cyan" => color::Fg(color::LightCyan).to_string(),
+        "light-white" => color::Fg(color::LightWhite).to_string(),
+        hex if hex.starts_with('#') => {
+            let hex = hex.get(1..).filter(|h| h.len() == 6)?;
+            let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+            color::Fg(color::Rgb(channel(0)?, channel(2)?, channel(4)?)).to_string()
+        }
+        n => color::Fg(color::AnsiValue(n.parse().ok()?)).to_string(),
+    })
+}
+
+/// Style of each role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub title: Style,
+    pub channel: Style,
+    pub duration: Style,
+    pub views: Style,
+    pub date: Style,
+    pub nsfw: Style,
+    pub error: Style,
+    pub warning: Style,
+    pub info: Style,
+    pub prompt: Style,
+    pub hint: Style,
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Theme> {
+        let styles = match name {
+            "dark" => [
+                "blue",
+                "green",
+                "yellow",
+                "green",
+                "green",
+                "red",
+                "bold+red",
+                "bold+yellow",
+                "bold+underline",
+                "bold",
+                "",
+            ],
+            "light" => [
+                "25",
+                "28",
+                "130",
+                "28",
+                "242",
+                "160",
+                "bold+160",
+                "bold+130",
+                "bold+underline",
+                "bold+25",
+                "245",
+            ],
+            "high-contrast" => [
+                "bold+light-white",
+                "light-green",
+                "light-yellow",
+                "light-green",
+                "white",
+                "bold+light-red",
+                "bold+light-red",
+                "bold+light-yellow",
+                "bold+underline+light-white",
+                "bold+light-cyan",
+                "white",
+            ],
+            _ => return None,
+        };
+        Some(Theme::from_specs(styles))
+    }
+
+    /// Used when the colors are disabled, only keeps some text attributes
+    pub fn plain() -> Theme {
+        Theme::from_specs([
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "bold",
+            "bold",
+            "bold+underline",
+            "bold",
+            "",
+        ])
+    }
+
+    fn from_specs(specs: [&str; 11]) -> Theme {
+        let [title, channel, duration, views, date, nsfw, error, warning, info, prompt, hint] =
+            specs.map(|s| Style::parse(s).expect("Invalid preset"));
+        Theme {
+            title,
+            channel,
+            duration,
+            views,
+            date,
+            nsfw,
+            error,
+            warning,
+            info,
+            prompt,
+            hint,
+        }
+    }
+
+    pub fn role(&self, role: &str) -> Option<&Style> {
+        Some(match role {
+            "title" => &self.title,
+            "channel" => &self.channel,
+            "duration" => &self.duration,
+            "views" => &self.views,
+            "date" => &self.date,
+            "nsfw" => &self.nsfw,
+            "error" => &self.error,
+            "warning" => &self.warning,
+            "info" => &self.info,
+            "prompt" => &self.prompt,
+            "hint" => &self.hint,
+            _ => return None,
+        })
+    }
+
+    pub fn role_mut(&mut self, role: &str) -> Option<&mut Style> {
+        Some(match role {
+            "title" => &mut self.title,
+            "channel" => &mut self.channel,
+            "duration" => &mut self.duration,
+            "views" => &mut self.views,
+            "date" => &mut self.date,
+            "nsfw" => &mut self.nsfw,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "info" => &mut self.info,
+            "prompt" => &mut self.prompt,
+            "hint" => &mut self.hint,
+            _ => return None,
+        })
+    }
+
+    /// Style of the layouts, each part is either a role or a style (ex: `title+underline`)
+    pub fn style(&self, spec: &str) -> Option<Style> {
+        let mut s = String::new();
+        for part in spec.split('+').map(str::trim).filter(|p| !p.is_empty()) {
+            match self.role(part) {
+                Some(role) => s.push_str(&role.0),
+                None => s.push_str(&basic_style(part)?),
+            }
+        }
+        Some(Style(s))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::preset(PRESETS[0]).expect("Invalid default preset")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn styles() {
+        assert_eq!(
+            Style::parse("bold+#268bd2"),
+            Some(Style(format!(
+                "{}{}",
+                style::Bold,
+                color::Fg(color::Rgb(0x26, 0x8b, 0xd2))
+            )))
+        );
+        assert_eq!(
+            Style::parse("33"),
+            Some(Style(color::Fg(color::AnsiValue(33)).to_string()))
+        );
+        assert_eq!(Style::parse(""), Some(Style::default()));
+        assert_eq!(Style::parse("#12345"), None);
+        assert_eq!(Style::parse("256"), None);
+        assert_eq!(Style::parse("pink"), None);
+
+        let theme = Theme::default();
+        assert_eq!(
+            theme.style("title+underline"),
+            Some(Style(format!(
+                "{}{}",
+                color::Fg(color::Blue),
+                style::Underline
+            )))
+        );
+        for preset in PRESETS {
+            assert!(Theme::preset(preset).is_some());
+        }
+        for role in ROLES {
+            assert!(Theme::plain().role_mut(role).is_some());
+        }
+    }
+}
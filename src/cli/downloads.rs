@@ -0,0 +1,624 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+mod metadata;
+
+use super::config::SidecarConf;
+use super::hooks::{self, Event, Hooks};
+use super::resolution::ResolutionPolicy;
+use peertube_api::{Instance, Video};
+use toml::value::{Array, Table, Value};
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::spawn;
+use std::time::Instant;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Cancelled)
+    }
+}
+
+struct Job {
+    uuid: String,
+    host: String,
+    title: String,
+    url: Option<String>,
+    path: Option<PathBuf>,
+    audio_only: bool,
+
+    /// Video from the current session, avoids fetching it again when resolving the url
+    video: Option<Arc<Video>>,
+    status: JobStatus,
+    /// A worker is handling the job, it stays set after a cancellation until the worker has stopped
+    running: bool,
+    downloaded: u64,
+    total: Option<u64>,
+
+    /// Time and number of bytes when the current run started, used to compute the speed
+    run_start: Option<(Instant, u64)>,
+}
+
+/// Snapshot of the state of a download, used for display
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub title: String,
+    pub status: JobStatus,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    /// Speed in bytes per second
+    pub speed: Option<u64>,
+    /// Estimated time remaining in seconds
+    pub eta: Option<u64>,
+}
+
+struct Shared {
+    jobs: Mutex<Vec<Job>>,
+    cond: Condvar,
+    instance: Arc<Instance>,
+    directory: PathBuf,
+    /// Same policy as for playing the videos
+    resolution: ResolutionPolicy,
+    sidecars: SidecarConf,
+    hooks: Hooks,
+    /// The hooks aren't run in private mode
+    private: AtomicBool,
+}
+
+/// Queue of videos downloaded in the background by worker threads
+pub struct DownloadManager {
+    shared: Arc<Shared>,
+}
+
+impl DownloadManager {
+    pub fn new(
+        instance: Arc<Instance>,
+        directory: PathBuf,
+        parallel: usize,
+        resolution: ResolutionPolicy,
+        sidecars: SidecarConf,
+        hooks: Hooks,
+    ) -> DownloadManager {
+        let shared = Arc::new(Shared {
+            jobs: Mutex::new(Vec::new()),
+            cond: Condvar::new(),
+            instance,
+            directory,
+            resolution,
+            sidecars,
+            hooks,
+            private: AtomicBool::new(false),
+        });
+
+        for _ in 0..parallel.max(1) {
+            let cloned = shared.clone();
+            spawn(move || worker(cloned));
+        }
+
+        DownloadManager { shared }
+    }
+
+    pub fn set_private(&self, private: bool) {
+        self.shared.private.store(private, Ordering::Relaxed);
+    }
+
+    pub fn add_video(&self, video: Arc<Video>, audio_only: bool) {
+        let mut jobs = self.shared.jobs.lock().unwrap();
+        jobs.push(Job {
+            uuid: video.uuid().to_owned(),
+            host: video.host().to_owned(),
+            title: video.name().to_owned(),
+            url: None,
+            path: None,
+            audio_only,
+            video: Some(video),
+            status: JobStatus::Queued,
+            running: false,
+            downloaded: 0,
+            total: None,
+            run_start: None,
+        });
+        self.shared.cond.notify_all();
+    }
+
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        let jobs = self.shared.jobs.lock().unwrap();
+        jobs.iter()
+            .map(|j| {
+                let speed = match (&j.status, j.run_start) {
+                    (JobStatus::Running, Some((start, bytes))) => {
+                        let elapsed = start.elapsed().as_secs_f64();
+                        if elapsed > 0.5 {
+                            Some(((j.downloaded - bytes) as f64 / elapsed) as u64)
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                };
+                let eta = match (speed, j.total) {
+                    (Some(s), Some(t)) if s > 0 => Some(t.saturating_sub(j.downloaded) / s),
+                    _ => None,
+                };
+                JobInfo {
+                    title: j.title.clone(),
+                    status: j.status.clone(),
+                    downloaded: j.downloaded,
+                    total: j.total,
+                    speed,
+                    eta,
+                }
+            })
+            .collect()
+    }
+
+    /// Cancel a job that isn't finished
+    /// Returns false if the job is already finished
+    pub fn cancel(&self, id: usize) -> bool {
+        let mut jobs = self.shared.jobs.lock().unwrap();
+        match jobs.get_mut(id) {
+            Some(job) if !job.status.is_finished() => {
+                if job.status != JobStatus::Running {
+                    remove_part(job.path.as_deref());
+                }
+                job.status = JobStatus::Cancelled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Put a failed or cancelled job back in the queue
+    /// Returns false if the job can't be retried, or if its worker hasn't stopped yet
+    pub fn retry(&self, id: usize) -> bool {
+        let mut jobs = self.shared.jobs.lock().unwrap();
+        match jobs.get_mut(id) {
+            Some(job)
+                if !job.running
+                    && matches!(job.status, JobStatus::Failed(_) | JobStatus::Cancelled) =>
+            {
+                job.status = JobStatus::Queued;
+                self.shared.cond.notify_all();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Path of a completed download
+    pub fn file(&self, id: usize) -> Option<PathBuf> {
+        let jobs = self.shared.jobs.lock().unwrap();
+        jobs.get(id)
+            .filter(|j| j.status == JobStatus::Done)
+            .and_then(|j| j.path.clone())
+    }
+
+    /// Load the jobs that weren't finished during a previous session
+    /// Returns the number of jobs that were put back in the queue
+    pub fn load_file(&self, path: &Path) -> io::Result<usize> {
+        let content = fs::read_to_string(path)?;
+        let table: Table = content
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut jobs = self.shared.jobs.lock().unwrap();
+        let mut resumed = 0;
+        for entry in table
+            .get("jobs")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(job) = Job::from_toml(entry) {
+                if job.status == JobStatus::Queued {
+                    resumed += 1;
+                }
+                jobs.push(job);
+            }
+        }
+        self.shared.cond.notify_all();
+        Ok(resumed)
+    }
+
+    /// Save the jobs that aren't finished so that they can be resumed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let jobs = self.shared.jobs.lock().unwrap();
+        let array: Array = jobs
+            .iter()
+            .filter(|j| !j.status.is_finished())
+            .map(Job::to_toml)
+            .collect();
+        if array.is_empty() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                res => res,
+            };
+        }
+        let mut table = Table::new();
+        table.insert("jobs".into(), Value::Array(array));
+        fs::write(path, table.to_string())
+    }
+}
+
+impl Job {
+    fn to_toml(&self) -> Value {
+        let mut t = Table::new();
+        t.insert("uuid".into(), Value::String(self.uuid.clone()));
+        t.insert("host".into(), Value::String(self.host.clone()));
+        t.insert("title".into(), Value::String(self.title.clone()));
+        if let Some(url) = &self.url {
+            t.insert("url".into(), Value::String(url.clone()));
+        }
+        if let Some(path) = self.path.as_ref().and_then(|p| p.to_str()) {
+            t.insert("path".into(), Value::String(path.to_owned()));
+        }
+        if self.audio_only {
+            t.insert("audio-only".into(), Value::Boolean(true));
+        }
+        match &self.status {
+            JobStatus::Failed(err) => {
+                t.insert("status".into(), Value::String("failed".into()));
+                t.insert("error".into(), Value::String(err.clone()));
+            }
+            _ => {
+                t.insert("status".into(), Value::String("queued".into()));
+            }
+        }
+        Value::Table(t)
+    }
+
+    fn from_toml(v: &Value) -> Option<Job> {
+        let t = v.as_table()?;
+        let get = |name: &str| t.get(name).and_then(Value::as_str).map(str::to_owned);
+        let status = match t.get("status").and_then(Value::as_str) {
+            Some("failed") => JobStatus::Failed(get("error").unwrap_or_default()),
+            _ => JobStatus::Queued,
+        };
+        Some(Job {
+            uuid: get("uuid")?,
+            host: get("host")?,
+            title: get("title")?,
+            url: get("url"),
+            path: get("path").map(PathBuf::from),
+            audio_only: t
+                .get("audio-only")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            video: None,
+            status,
+            running: false,
+            downloaded: 0,
+            total: None,
+            run_start: None,
+        })
+    }
+}
+
+fn worker(shared: Arc<Shared>) {
+    loop {
+        let (id, video, uuid, host, url, path, audio_only) = {
+            let mut jobs = shared.jobs.lock().unwrap();
+            let id = loop {
+                if let Some(id) = jobs.iter().position(|j| j.status == JobStatus::Queued) {
+                    break id;
+                }
+                jobs = shared.cond.wait(jobs).unwrap();
+            };
+            let job = &mut jobs[id];
+            job.status = JobStatus::Running;
+            job.running = true;
+            job.run_start = None;
+            (
+                id,
+                job.video.clone(),
+                job.uuid.clone(),
+                job.host.clone(),
+                job.url.clone(),
+                job.path.clone(),
+                job.audio_only,
+            )
+        };
+
+        let res = resolve(&shared, video, &uuid, &host, url, audio_only).and_then(|url| {
+            // The path is chosen under the lock so that two workers can't pick the same one
+            let path = {
+                let mut jobs = shared.jobs.lock().unwrap();
+                let path = match path {
+                    Some(p) => p,
+                    None => {
+                        let used: Vec<&Path> = jobs
+                            .iter()
+                            .enumerate()
+                            .filter(|(k, _)| *k != id)
+                            .filter_map(|(_, j)| j.path.as_deref())
+                            .collect();
+                        file_path(&shared.directory, &jobs[id].title, &uuid, &url, &used)
+                    }
+                };
+                jobs[id].url = Some(url.clone());
+                jobs[id].path = Some(path.clone());
+                path
+            };
+            download(&shared, id, &url, &path)?;
+            if !shared.sidecars.is_empty()
+                && shared.jobs.lock().unwrap()[id].status != JobStatus::Cancelled
+            {
+                metadata::write_sidecars(&shared.instance, shared.sidecars, &host, &uuid, &path)
+                    .map_err(|e| format!("The video was saved but not its metadata: {e}"))?;
+            }
+            Ok(())
+        });
+
+        let mut jobs = shared.jobs.lock().unwrap();
+        let job = &mut jobs[id];
+        job.running = false;
+        match res {
+            Ok(()) if job.status == JobStatus::Cancelled => remove_part(job.path.as_deref()),
+            Ok(()) => {
+                job.status = JobStatus::Done;
+                let mut data = match &job.video {
+                    Some(v) => hooks::video_data(v),
+                    None => vec![
+                        ("uuid", job.uuid.clone()),
+                        ("title", job.title.clone()),
+                        ("host", job.host.clone()),
+                    ],
+                };
+                if let Some(path) = &job.path {
+                    data.push(("path", path.display().to_string()));
+                }
+                let private = shared.private.load(Ordering::Relaxed);
+                shared
+                    .hooks
+                    .run_unless_private(private, Event::DownloadFinished, &data);
+            }
+            Err(_) if job.status == JobStatus::Cancelled => remove_part(job.path.as_deref()),
+            Err(err) => job.status = JobStatus::Failed(err),
+        }
+    }
+}
+
+/// Get the url of the file to download
+fn resolve(
+    shared: &Shared,
+    video: Option<Arc<Video>>,
+    uuid: &str,
+    host: &str,
+    url: Option<String>,
+    audio_only: bool,
+) -> Result<String, String> {
+    if let Some(url) = url {
+        return Ok(url);
+    }
+
+    let video = match video {
+        Some(v) => v,
+        None => Arc::new(
+            shared
+                .instance
+                .single_video(host, uuid)
+                .map_err(|e| e.to_string())?,
+        ),
+    };
+    video.load_resolutions().map_err(|e| e.to_string())?;
+    if audio_only {
+        return video.audio_url().map_err(|e| e.to_string());
+    }
+    if !video.has_files().map_err(|e| e.to_string())? {
+        return Err("No downloadable file is available for this video".into());
+    }
+    let resolutions: Vec<u64> = video
+        .resolutions()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|r| *r.id())
+        .collect();
+    let id = shared.resolution.pick(&resolutions).unwrap_or(0);
+    video.resolution_url(id).map_err(|e| e.to_string())
+}
+
+fn download(shared: &Shared, id: usize, url: &str, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let part = part_path(path);
+    if path.exists() && !part.exists() {
+        // Already downloaded, only the metadata failed
+        return Ok(());
+    }
+    let already = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+
+    let mut req = ureq::get(url);
+    if already > 0 {
+        req = req.set("Range", &format!("bytes={already}-"));
+    }
+    let resp = req.call().map_err(|e| e.to_string())?;
+
+    let resumed = resp.status() == 206;
+    let start = if resumed { already } else { 0 };
+    let total = if resumed {
+        resp.header("Content-Range")
+            .and_then(|r| r.rsplit('/').next())
+            .and_then(|t| t.parse().ok())
+    } else {
+        resp.header("Content-Length").and_then(|l| l.parse().ok())
+    };
+
+    let mut file = if resumed {
+        OpenOptions::new().append(true).open(&part)
+    } else {
+        File::create(&part)
+    }
+    .map_err(|e| e.to_string())?;
+
+    {
+        let mut jobs = shared.jobs.lock().unwrap();
+        let job = &mut jobs[id];
+        job.downloaded = start;
+        job.total = total;
+        job.run_start = Some((Instant::now(), start));
+    }
+
+    let mut reader = resp.into_reader();
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+        file.write_all(&buf[..read]).map_err(|e| e.to_string())?;
+
+        let mut jobs = shared.jobs.lock().unwrap();
+        let job = &mut jobs[id];
+        job.downloaded += read as u64;
+        if job.status == JobStatus::Cancelled {
+            return Ok(());
+        }
+    }
+
+    file.flush().map_err(|e| e.to_string())?;
+    fs::rename(&part, path).map_err(|e| e.to_string())
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+fn remove_part(path: Option<&Path>) {
+    if let Some(p) = path {
+        fs::remove_file(part_path(p)).unwrap_or(());
+    }
+}
+
+/// Build the path where a video will be saved
+/// The uuid is added to the name only if a file with the same title already exists or is `used`
+/// by another download
+fn file_path(directory: &Path, title: &str, uuid: &str, url: &str, used: &[&Path]) -> PathBuf {
+    let extension = url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.len() <= 5 && ext.chars().all(char::is_alphanumeric))
+        .unwrap_or("mp4");
+
+    let name = sanitize_file_name(title);
+    let path = directory.join(format!("{name}.{extension}"));
+    if path.exists() || part_path(&path).exists() || used.contains(&path.as_path()) {
+        directory.join(format!("{name}-{uuid}.{extension}"))
+    } else {
+        path
+    }
+}
+
+fn sanitize_file_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(120)
+        .collect();
+    let name = name.trim().trim_start_matches('.');
+    if name.is_empty() {
+        "video".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn file_names() {
+        assert_eq!(sanitize_file_name("What is PeerTube?"), "What is PeerTube_");
+        assert_eq!(sanitize_file_name("a/b\\c"), "a_b_c");
+        assert_eq!(sanitize_file_name(" ..hidden "), "hidden");
+        assert_eq!(sanitize_file_name("   "), "video");
+        assert_eq!(
+            file_path(
+                Path::new("/does/not/exist"),
+                "Title",
+                "uuid",
+                "https://host/download/videos/uuid-720.webm",
+                &[]
+            ),
+            PathBuf::from("/does/not/exist/Title.webm")
+        );
+        assert_eq!(
+            file_path(
+                Path::new("/does/not/exist"),
+                "Title",
+                "uuid",
+                "https://host/download/videos/uuid-720.webm",
+                &[Path::new("/does/not/exist/Title.webm")]
+            ),
+            PathBuf::from("/does/not/exist/Title-uuid.webm")
+        );
+        assert_eq!(
+            file_path(
+                Path::new("/does/not/exist"),
+                "Title",
+                "uuid",
+                "https://host/no_ext",
+                &[]
+            ),
+            PathBuf::from("/does/not/exist/Title.mp4")
+        );
+    }
+
+    #[test]
+    fn job_serialization() {
+        let job = Job {
+            uuid: "uuid".into(),
+            host: "host.org".into(),
+            title: "title".into(),
+            url: Some("https://host.org/file.mp4".into()),
+            path: Some("/tmp/title.mp4".into()),
+            audio_only: true,
+            video: None,
+            status: JobStatus::Failed("Connection error".into()),
+            running: false,
+            downloaded: 42,
+            total: Some(1000),
+            run_start: None,
+        };
+        let loaded = Job::from_toml(&job.to_toml()).unwrap();
+        assert_eq!(loaded.uuid, job.uuid);
+        assert_eq!(loaded.host, job.host);
+        assert_eq!(loaded.title, job.title);
+        assert_eq!(loaded.url, job.url);
+        assert_eq!(loaded.path, job.path);
+        assert_eq!(loaded.audio_only, job.audio_only);
+        assert_eq!(loaded.status, job.status);
+        assert_eq!(loaded.downloaded, 0);
+    }
+}
@@ -0,0 +1,208 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::cli::config::{MetadataFormat, SidecarConf};
+use peertube_api::{Instance, Video};
+
+use serde_json::json;
+use time::{format_description, format_description::well_known::Rfc3339};
+
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Write the metadata, thumbnail and captions next to a downloaded video
+pub fn write_sidecars(
+    instance: &Arc<Instance>,
+    conf: SidecarConf,
+    host: &str,
+    uuid: &str,
+    path: &Path,
+) -> Result<(), String> {
+    // Videos from search results don't have all the metadata
+    let video = instance
+        .single_video(host, uuid)
+        .map_err(|e| e.to_string())?;
+
+    match conf.metadata {
+        MetadataFormat::None => {}
+        MetadataFormat::Json => {
+            fs::write(sibling(path, ".json"), json_metadata(&video)).map_err(|e| e.to_string())?
+        }
+        MetadataFormat::Nfo => {
+            fs::write(sibling(path, ".nfo"), nfo_metadata(&video)).map_err(|e| e.to_string())?
+        }
+    }
+
+    if conf.thumbnail {
+        if let Some(thumbnail) = video.thumbnail_path() {
+            let extension = safe_part(
+                thumbnail.rsplit_once('.').map(|(_, e)| e).unwrap_or(""),
+                "jpg",
+            );
+            fetch_to_file(
+                &instance.resource_url(host, thumbnail),
+                &sibling(path, &format!("-thumb.{extension}")),
+            )?;
+        }
+    }
+
+    if conf.captions {
+        for (language, url) in instance
+            .video_captions(host, uuid)
+            .map_err(|e| e.to_string())?
+        {
+            let language = safe_part(&language, "und");
+            fetch_to_file(&url, &sibling(path, &format!(".{language}.vtt")))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Path of a file next to the video, with the same name and a different suffix
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}{suffix}"))
+}
+
+/// Value from the server used in a file name, or `fallback` if it could leave the directory
+fn safe_part<'a>(value: &'a str, fallback: &'a str) -> &'a str {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        value
+    } else {
+        fallback
+    }
+}
+
+fn fetch_to_file(url: &str, path: &Path) -> Result<(), String> {
+    let resp = ureq::get(url).call().map_err(|e| e.to_string())?;
+    let mut file = File::create(path).map_err(|e| e.to_string())?;
+    io::copy(&mut resp.into_reader(), &mut file).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn json_metadata(video: &Video) -> String {
+    let value = json!({
+        "uuid": video.uuid(),
+        "title": video.name(),
+        "channel": {
+            "name": video.channel_display(),
+            "handle": video.channel_handle(),
+        },
+        "account": {
+            "name": video.account_display(),
+            "handle": video.account_handle(),
+        },
+        "host": video.host(),
+        "description": video.description().ok().flatten(),
+        "tags": video.tags(),
+        "category": video.category(),
+        "licence": video.licence(),
+        "language": video.language(),
+        "published": video.published().format(&Rfc3339).ok(),
+        "duration": video.duration(),
+        "url": video.watch_url(),
+    });
+    serde_json::to_string_pretty(&value).expect("Serializing metadata failed unexpectedly")
+}
+
+fn nfo_metadata(video: &Video) -> String {
+    let date = format_description::parse("[year]-[month]-[day]").unwrap();
+    let mut nfo = String::new();
+    nfo.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<movie>\n");
+    let mut tag = |name: &str, value: &str| {
+        writeln!(nfo, "  <{name}>{}</{name}>", xml_escape(value))
+            .expect("Formatting failed unexpectedly");
+    };
+
+    tag("title", video.name());
+    if let Ok(Some(description)) = video.description() {
+        tag("plot", &description);
+    }
+    tag("studio", video.channel_display());
+    tag("credits", video.account_display());
+    for t in video.tags() {
+        tag("tag", t);
+    }
+    if let Some(category) = video.category() {
+        tag("genre", category);
+    }
+    if let Some(language) = video.language() {
+        tag("language", language);
+    }
+    if let Some(licence) = video.licence() {
+        tag("licence", licence);
+    }
+    if let Ok(published) = video.published().format(&date) {
+        tag("premiered", &published);
+    }
+    tag("year", &video.published().year().to_string());
+    tag("runtime", &(video.duration() / 60).to_string());
+    tag("url", &video.watch_url());
+    writeln!(
+        nfo,
+        "  <uniqueid type=\"peertube\" default=\"true\">{}</uniqueid>",
+        xml_escape(video.uuid())
+    )
+    .expect("Formatting failed unexpectedly");
+    nfo.push_str("</movie>\n");
+    nfo
+}
+
+fn xml_escape(input: &str) -> String {
+    let mut s = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' => s.push_str("&quot;"),
+            '\'' => s.push_str("&apos;"),
+            c => s.push(c),
+        }
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn escape() {
+        assert_eq!(xml_escape("Tom & Jerry <3"), "Tom &amp; Jerry &lt;3");
+        assert_eq!(xml_escape("\"quoted\""), "&quot;quoted&quot;");
+    }
+
+    #[test]
+    fn siblings() {
+        assert_eq!(
+            sibling(Path::new("/videos/Some title.mp4"), ".nfo"),
+            PathBuf::from("/videos/Some title.nfo")
+        );
+        assert_eq!(
+            sibling(Path::new("/videos/v1.2.webm"), "-thumb.jpg"),
+            PathBuf::from("/videos/v1.2-thumb.jpg")
+        );
+        assert_eq!(safe_part("pt-BR", "und"), "pt-BR");
+        assert_eq!(safe_part("../../.bashrc", "und"), "und");
+        assert_eq!(safe_part("x/../../y", "jpg"), "jpg");
+        assert_eq!(safe_part("", "jpg"), "jpg");
+    }
+}
@@ -6,64 +6,274 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use peertube_api::Video;
+use time::OffsetDateTime;
+use toml::value::{Array, Table, Value};
+
+use std::collections::HashMap;
 
 use std::fmt::Write;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Error};
+use std::fs;
+use std::io::{Error, ErrorKind};
 use std::path::Path;
 
+/// Version of the format of the history file
+const HISTORY_VERSION: i64 = 1;
+
+/// Video of the viewing history
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub uuid: String,
+    /// Empty for the entries migrated from the format that only stored the uuid
+    pub host: String,
+    pub title: String,
+    pub handle: String,
+    pub duration: u64,
+    /// Unix timestamp of the last view, 0 when unknown
+    pub viewed_at: i64,
+}
+
+impl Entry {
+    fn to_toml(&self) -> Value {
+        let mut t = Table::new();
+        t.insert("uuid".into(), Value::String(self.uuid.clone()));
+        t.insert("host".into(), Value::String(self.host.clone()));
+        t.insert("title".into(), Value::String(self.title.clone()));
+        t.insert("handle".into(), Value::String(self.handle.clone()));
+        t.insert("duration".into(), Value::Integer(self.duration as i64));
+        t.insert("viewed-at".into(), Value::Integer(self.viewed_at));
+        Value::Table(t)
+    }
+
+    fn from_toml(v: &Value) -> Option<Entry> {
+        let string = |name| v.get(name).and_then(Value::as_str).unwrap_or("").to_owned();
+        Some(Entry {
+            uuid: v.get("uuid")?.as_str()?.to_owned(),
+            host: string("host"),
+            title: string("title"),
+            handle: string("handle"),
+            duration: v.get("duration").and_then(Value::as_integer).unwrap_or(0) as u64,
+            viewed_at: v.get("viewed-at").and_then(Value::as_integer).unwrap_or(0),
+        })
+    }
+
+    /// Whether the title, the channel or the host contains the query, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.title, &self.handle, &self.host]
+            .iter()
+            .any(|s| s.to_lowercase().contains(&query))
+    }
+}
+
+/// Line of the format with one uuid per line
+fn is_legacy_line(line: &str) -> bool {
+    line.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
 pub struct History {
-    videos: HashSet<String>,
+    entries: HashMap<String, Entry>,
+    /// Uuids from the oldest to the latest view
     order: Vec<String>,
+
+    /// Position in seconds of the videos that were partly watched
+    positions: HashMap<String, u64>,
+
+    /// Set when the file comes from a newer version, so that it isn't overwritten
+    read_only: bool,
+    /// In private mode, no view nor position is recorded
+    private: bool,
 }
 
 impl History {
     pub fn new() -> History {
         History {
-            videos: HashSet::new(),
+            entries: HashMap::new(),
             order: Vec::new(),
+            positions: HashMap::new(),
+            read_only: false,
+            private: false,
         }
     }
 
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+    }
+
+    /// Load the history, files with one uuid per line from older versions are migrated
     pub fn load_file(&mut self, path: &Path) -> Result<(), Error> {
-        let file = File::open(path)?;
-        let buf_reader = BufReader::new(file);
-        let mut reversed = Vec::new();
-
-        for line in buf_reader.lines() {
-            let line_unwrapped = line?;
-            if !self.videos.contains(&line_unwrapped) {
-                self.videos.insert(line_unwrapped.clone());
-                reversed.push(line_unwrapped);
+        let content = match fs::read_to_string(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                self.read_only = true;
+                return Err(err);
             }
+            res => res?,
+        };
+        // The latest views come first in the file
+        let mut entries = match content.parse::<Table>() {
+            Ok(table) if table.contains_key("version") => {
+                let version = table.get("version").and_then(Value::as_integer);
+                if version != Some(HISTORY_VERSION) {
+                    self.read_only = true;
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Unsupported history version: {version:?}"),
+                    ));
+                }
+                table
+                    .get("videos")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Entry::from_toml)
+                    .collect()
+            }
+            _ if content.lines().all(is_legacy_line) => content
+                .lines()
+                .filter(|l| !l.is_empty())
+                .map(|uuid| Entry {
+                    uuid: uuid.to_owned(),
+                    host: String::new(),
+                    title: String::new(),
+                    handle: String::new(),
+                    duration: 0,
+                    viewed_at: 0,
+                })
+                .collect::<Vec<_>>(),
+            // Truncated or edited by hand, saving would replace it
+            Ok(_) => {
+                self.read_only = true;
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Unrecognized history format",
+                ));
+            }
+            Err(err) => {
+                self.read_only = true;
+                return Err(Error::new(ErrorKind::InvalidData, err));
+            }
+        };
+
+        entries.reverse();
+        for entry in entries {
+            self.add_entry(entry);
         }
-        self.order = reversed.into_iter().rev().collect();
         Ok(())
     }
 
-    pub fn add_video(&mut self, uuid: String) {
-        if !self.videos.contains(&uuid) {
-            self.videos.insert(uuid.clone());
-            self.order.push(uuid);
+    pub fn add_video(&mut self, video: &Video) {
+        if self.private {
+            return;
         }
+        self.add_entry(Entry {
+            uuid: video.uuid().to_owned(),
+            host: video.host().to_owned(),
+            title: video.name().to_owned(),
+            handle: video.channel_handle(),
+            duration: video.duration(),
+            viewed_at: OffsetDateTime::now_utc().unix_timestamp(),
+        });
     }
 
-    pub fn save(&self, path: &Path, max_len: usize) -> Result<(), Error> {
-        let mut already_in = HashSet::new();
-        let mut full_str = String::new();
-        let mut lines = 0;
-        for uuid in self.order.iter().rev() {
-            if !already_in.contains(uuid) {
-                already_in.insert(uuid);
-                writeln!(full_str, "{uuid}").expect("Formating failed unexpectedly");
-                lines += 1;
-                if lines == max_len {
-                    break;
+    /// Add an entry as the latest view, replacing the previous view of the same video
+    fn add_entry(&mut self, entry: Entry) {
+        if self.entries.contains_key(&entry.uuid) {
+            self.order.retain(|uuid| uuid != &entry.uuid);
+        }
+        self.order.push(entry.uuid.clone());
+        self.entries.insert(entry.uuid.clone(), entry);
+    }
+
+    /// Entries matching the query, from the latest view
+    pub fn entries(&self, query: &str) -> Vec<&Entry> {
+        self.order
+            .iter()
+            .rev()
+            .map(|uuid| &self.entries[uuid])
+            .filter(|e| e.matches(query))
+            .collect()
+    }
+
+    /// Remove a video from the history, returns false if it wasn't in it
+    pub fn forget(&mut self, uuid: &str) -> bool {
+        self.positions.remove(uuid);
+        if self.entries.remove(uuid).is_some() {
+            self.order.retain(|u| u != uuid);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Number of videos partly watched which aren't in the history
+    pub fn abandoned(&self) -> usize {
+        self.positions
+            .keys()
+            .filter(|uuid| !self.entries.contains_key(*uuid))
+            .count()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.positions.clear();
+    }
+
+    pub fn position(&self, uuid: &str) -> Option<u64> {
+        self.positions.get(uuid).copied()
+    }
+
+    pub fn set_position(&mut self, uuid: String, position: u64) {
+        if self.private {
+            return;
+        }
+        self.positions.insert(uuid, position);
+    }
+
+    pub fn remove_position(&mut self, uuid: &str) {
+        self.positions.remove(uuid);
+    }
+
+    /// Load the positions, stored as one `<uuid> <seconds>` pair per line
+    pub fn load_positions(&mut self, path: &Path) -> Result<(), Error> {
+        for line in fs::read_to_string(path)?.lines() {
+            if let Some((uuid, pos)) = line.split_once(' ') {
+                if let Ok(pos) = pos.parse() {
+                    self.positions.insert(uuid.to_owned(), pos);
                 }
             }
         }
-        std::fs::write(path, &full_str)
+        Ok(())
+    }
+
+    pub fn save_positions(&self, path: &Path) -> Result<(), Error> {
+        let mut full_str = String::new();
+        for (uuid, pos) in &self.positions {
+            writeln!(full_str, "{uuid} {pos}").expect("Formating failed unexpectedly");
+        }
+        fs::write(path, &full_str)
+    }
+
+    /// Save the `max_len` latest views
+    pub fn save(&self, path: &Path, max_len: usize) -> Result<(), Error> {
+        if self.read_only {
+            return Ok(());
+        }
+        fs::write(path, self.to_toml(max_len))
+    }
+
+    fn to_toml(&self, max_len: usize) -> String {
+        let videos: Array = self
+            .order
+            .iter()
+            .rev()
+            .take(max_len)
+            .map(|uuid| self.entries[uuid].to_toml())
+            .collect();
+        let mut table = Table::new();
+        table.insert("version".into(), Value::Integer(HISTORY_VERSION));
+        table.insert("videos".into(), Value::Array(videos));
+        table.to_string()
     }
 }
 
@@ -73,13 +283,13 @@ pub trait HistoryT<D: ?Sized> {
 
 impl HistoryT<str> for History {
     fn is_viewed(&self, uuid: &str) -> bool {
-        self.videos.contains(uuid)
+        self.entries.contains_key(uuid)
     }
 }
 
 impl HistoryT<peertube_api::Video> for History {
     fn is_viewed(&self, video: &peertube_api::Video) -> bool {
-        self.videos.contains(video.uuid())
+        self.entries.contains_key(video.uuid())
     }
 }
 
@@ -88,3 +298,82 @@ impl<T: ?Sized> HistoryT<T> for () {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(uuid: &str, title: &str, viewed_at: i64) -> Entry {
+        Entry {
+            uuid: uuid.into(),
+            host: "framatube.org".into(),
+            title: title.into(),
+            handle: "framasoft@framatube.org".into(),
+            duration: 300,
+            viewed_at,
+        }
+    }
+
+    #[test]
+    fn migration() {
+        let path = std::env::temp_dir().join(format!("ptv-history-test-{}", std::process::id()));
+        fs::write(&path, "latest\nfirst\n").unwrap();
+        let mut history = History::new();
+        history.load_file(&path).unwrap();
+        let uuids: Vec<_> = history.entries("").iter().map(|e| &*e.uuid).collect();
+        assert_eq!(uuids, vec!["latest", "first"]);
+        assert!(history.is_viewed("first"));
+
+        history.add_entry(entry("new", "Rust talk", 1_700_000_000));
+        history.save(&path, 2).unwrap();
+        let mut reloaded = History::new();
+        reloaded.load_file(&path).unwrap();
+        assert_eq!(reloaded.entries(""), history.entries("")[..2].to_vec());
+        assert!(!reloaded.is_viewed("first"));
+
+        // Files from newer versions are left untouched
+        fs::write(&path, "version = 2\n").unwrap();
+        let mut newer = History::new();
+        assert!(newer.load_file(&path).is_err());
+        newer.add_entry(entry("new", "Rust talk", 1_700_000_000));
+        newer.save(&path, 10).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "version = 2\n");
+
+        // Neither are files that can't be parsed
+        let broken = "version = 1\n[[videos]]\ntitle = \"Rust";
+        fs::write(&path, broken).unwrap();
+        let mut broken_history = History::new();
+        assert!(broken_history.load_file(&path).is_err());
+        assert!(broken_history.entries("").is_empty());
+        broken_history.add_entry(entry("new", "Rust talk", 1_700_000_000));
+        broken_history.save(&path, 10).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), broken);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn entries() {
+        let mut history = History::new();
+        history.add_entry(entry("a", "Rust talk", 1));
+        history.add_entry(entry("b", "Peertube", 2));
+        history.add_entry(entry("a", "Rust talk", 3));
+        assert_eq!(
+            history.entries(""),
+            vec![&entry("a", "Rust talk", 3), &entry("b", "Peertube", 2)]
+        );
+        assert_eq!(history.entries("RUST"), vec![&entry("a", "Rust talk", 3)]);
+        assert_eq!(history.entries("framasoft").len(), 2);
+
+        history.set_position("a".into(), 42);
+        assert!(history.forget("a"));
+        assert!(!history.forget("a"));
+        assert_eq!(history.position("a"), None);
+        history.clear();
+        assert!(history.entries("").is_empty());
+
+        history.set_private(true);
+        history.set_position("b".into(), 42);
+        assert_eq!(history.position("b"), None);
+    }
+}
@@ -0,0 +1,174 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use peertube_api::Video;
+use serde_json::{Map, Value};
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread::{spawn, JoinHandle};
+
+/// Event triggering a hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Play,
+    DownloadFinished,
+    Error,
+    Quit,
+}
+
+impl Event {
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::Play => "play",
+            Event::DownloadFinished => "download-finished",
+            Event::Error => "error",
+            Event::Quit => "quit",
+        }
+    }
+}
+
+/// Programs ran on events, each one is the program followed by its arguments
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Hooks {
+    pub on_play: Vec<String>,
+    pub on_download_finished: Vec<String>,
+    pub on_error: Vec<String>,
+    pub on_quit: Vec<String>,
+}
+
+impl Hooks {
+    fn command(&self, event: Event) -> &[String] {
+        match event {
+            Event::Play => &self.on_play,
+            Event::DownloadFinished => &self.on_download_finished,
+            Event::Error => &self.on_error,
+            Event::Quit => &self.on_quit,
+        }
+    }
+
+    /// Run the hook of the event in the background, unless in private mode
+    ///
+    /// The hooks get the watched videos so they must not run in private mode
+    /// Returns None if no hook was run
+    pub fn run_unless_private(
+        &self,
+        private: bool,
+        event: Event,
+        data: &[(&str, String)],
+    ) -> Option<JoinHandle<()>> {
+        if private {
+            return None;
+        }
+        self.run(event, data)
+    }
+
+    /// Run the hook of the event in the background
+    ///
+    /// The data is given both in `PTV_*` environment variables and as a JSON object on stdin
+    /// Returns None if no hook is set for this event
+    fn run(&self, event: Event, data: &[(&str, String)]) -> Option<JoinHandle<()>> {
+        let (program, args) = self.command(event).split_first()?;
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .env("PTV_EVENT", event.name())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        for (name, value) in data {
+            command.env(env_name(name), value);
+        }
+
+        let json = to_json(event, data);
+        Some(spawn(move || {
+            if let Ok(mut child) = command.spawn() {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // The hook may not read its stdin
+                    stdin.write_all(json.as_bytes()).unwrap_or(());
+                }
+                child.wait().ok();
+            }
+        }))
+    }
+}
+
+/// Metadata of the video given to the hooks
+pub fn video_data(video: &Video) -> Vec<(&'static str, String)> {
+    vec![
+        ("uuid", video.uuid().to_owned()),
+        ("title", video.name().to_owned()),
+        ("host", video.host().to_owned()),
+        ("channel", video.channel_display().to_owned()),
+        ("handle", video.channel_handle()),
+        ("watch_url", video.watch_url()),
+        ("duration", video.duration().to_string()),
+    ]
+}
+
+fn env_name(name: &str) -> String {
+    format!("PTV_{}", name.to_uppercase())
+}
+
+fn to_json(event: Event, data: &[(&str, String)]) -> String {
+    let mut map = Map::new();
+    map.insert("event".into(), event.name().into());
+    for (name, value) in data {
+        map.insert((*name).into(), value.as_str().into());
+    }
+    Value::Object(map).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn data() {
+        let data = [
+            ("title", "A \"talk\"".to_string()),
+            ("watch_url", "https://framatube.org/w/1".to_string()),
+        ];
+        assert_eq!(env_name("watch_url"), "PTV_WATCH_URL");
+        assert_eq!(
+            to_json(Event::DownloadFinished, &data),
+            r#"{"event":"download-finished","title":"A \"talk\"","watch_url":"https://framatube.org/w/1"}"#
+        );
+        assert!(Hooks::default().run(Event::Play, &data).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run() {
+        let path = std::env::temp_dir().join(format!("ptv-hook-test-{}", std::process::id()));
+        let hooks = Hooks {
+            on_quit: vec![
+                "sh".into(),
+                "-c".into(),
+                "echo \"$PTV_EVENT $PTV_TITLE\" > \"$0\"; cat >> \"$0\"".into(),
+                path.to_str().unwrap().into(),
+            ],
+            ..Hooks::default()
+        };
+        assert!(hooks
+            .run_unless_private(true, Event::Quit, &[("title", "Bye".to_string())])
+            .is_none());
+        assert!(!path.exists());
+        hooks
+            .run_unless_private(false, Event::Quit, &[("title", "Bye".to_string())])
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "quit Bye\n{\"event\":\"quit\",\"title\":\"Bye\"}"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
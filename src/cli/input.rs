@@ -8,6 +8,7 @@
 
 mod helper;
 
+use crate::cli::display::theme::Theme;
 use crate::error;
 pub use helper::Message;
 use helper::{Helper, Stade};
@@ -22,39 +23,83 @@ use std::path::Path;
 use rustyline::{
     config::{Builder, EditMode},
     history::FileHistory,
+    ExternalPrinter,
 };
+use signal_hook::{consts::SIGWINCH, iterator::Signals};
+use termion::{clear, cursor};
 
-use super::parser::{filter_high_ids, parse, parse_first, parse_id, ParsedQuery};
+use super::parser::{filter_high_ids, parse_first, parse_id, parse_with, ParsedQuery};
 use preloadable_list::{AsyncLoader, PreloadableList};
 
 pub struct HelpedHandle<'editor> {
     rx: &'editor mut Receiver<Message>,
+    printer: &'editor mut Option<Box<dyn ExternalPrinter + Send>>,
 }
 
 impl<'editor> HelpedHandle<'editor> {
     pub fn next(&mut self) -> Message {
         self.rx.recv().unwrap()
     }
+
+    /// Clear the screen and print `content` above the prompt
+    pub fn redraw(&mut self, content: String) {
+        if let Some(printer) = self.printer {
+            printer
+                .print(format!("{}{}{}", clear::All, cursor::Goto(1, 1), content))
+                .ok();
+        }
+    }
+}
+
+/// Send `Message::Resized` each time the terminal is resized
+fn watch_resize(tx: Sender<Message>) {
+    if let Ok(mut signals) = Signals::new([SIGWINCH]) {
+        spawn(move || {
+            for _ in signals.forever() {
+                if tx.send(Message::Resized).is_err() {
+                    break;
+                }
+            }
+        });
+    }
 }
 
 pub struct Editor {
     rx: Receiver<Message>,
     tx: Sender<Message>,
     rl: Arc<Mutex<rustyline::Editor<Helper, FileHistory>>>,
+    customs: &'static [&'static str],
+    private: bool,
+    /// Prints while a prompt is displayed
+    printer: Option<Box<dyn ExternalPrinter + Send>>,
 }
 
 impl Editor {
-    pub fn new(edit_mode: EditMode, use_color: bool) -> Result<Editor, error::Error> {
-        let (rx, tx, h) = Helper::new(use_color);
+    /// `customs` are the commands defined in the config
+    pub fn new(
+        edit_mode: EditMode,
+        use_color: bool,
+        theme: &Theme,
+        customs: &'static [&'static str],
+    ) -> Result<Editor, error::Error> {
+        let (rx, tx, h) = Helper::new(use_color, theme, customs);
         let mut rl = rustyline::Editor::with_history(
             Builder::new().edit_mode(edit_mode).build(),
             FileHistory::new(),
         )?;
         rl.set_helper(Some(h));
+        let printer = rl
+            .create_external_printer()
+            .ok()
+            .map(|p| Box::new(p) as Box<dyn ExternalPrinter + Send>);
+        watch_resize(tx.clone());
         Ok(Editor {
             rx,
             tx,
             rl: Arc::new(Mutex::new(rl)),
+            customs,
+            private: false,
+            printer,
         })
     }
 
@@ -72,9 +117,9 @@ impl Editor {
             match res {
                 Ok(l) => {
                     let parsed = if let Some(id) = limit {
-                        filter_high_ids(parse(&l), id)
+                        filter_high_ids(parse_with(&l, self.customs), id)
                     } else {
-                        parse(&l)
+                        parse_with(&l, self.customs)
                     };
                     if let Ok(q) = parsed {
                         return Ok(q);
@@ -154,6 +199,9 @@ impl Editor {
     pub fn helped_readline(&mut self, prompt: String, limit: Option<usize>) -> HelpedHandle<'_> {
         let rl_cloned = self.rl.clone();
         let tx_cloned = self.tx.clone();
+        let customs = self.customs;
+        // Resizes that happened before the prompt are already taken into account
+        self.rx.try_iter().for_each(drop);
         spawn(move || {
             let mut ed = rl_cloned.lock().unwrap();
             if let Some(h) = ed.helper_mut() {
@@ -165,9 +213,9 @@ impl Editor {
                 match res {
                     Ok(line) => {
                         let parsed = if let Some(l) = limit {
-                            filter_high_ids(parse(&line), l)
+                            filter_high_ids(parse_with(&line, customs), l)
                         } else {
-                            parse(&line)
+                            parse_with(&line, customs)
                         };
                         if let Ok(p) = parsed {
                             tx_cloned.send(Message::Over(Ok(p))).unwrap();
@@ -185,13 +233,18 @@ impl Editor {
                 break;
             }
         });
-        HelpedHandle { rx: &mut self.rx }
+        HelpedHandle {
+            rx: &mut self.rx,
+            printer: &mut self.printer,
+        }
     }
 
+    /// `render` displays the list again when the terminal is resized
     pub fn autoload_readline<Loader: AsyncLoader>(
         &mut self,
         prompt: String,
         list: &mut PreloadableList<Loader>,
+        render: impl Fn(&PreloadableList<Loader>) -> String,
     ) -> rustyline::Result<ParsedQuery> {
         let mut handle = self.helped_readline(prompt, Some(list.current().len() + 1));
         loop {
@@ -199,6 +252,7 @@ impl Editor {
                 Message::Over(res) => {
                     return res;
                 }
+                Message::Resized => handle.redraw(render(list)),
                 Message::Unfinnished(ParsedQuery::Next) => list.preload_next(),
                 Message::Unfinnished(p) => {
                     if let Some(id) = p.should_preload() {
@@ -220,7 +274,15 @@ impl Editor {
     }
 
     pub fn add_history_entry(&mut self, entry: &str) {
+        if self.private {
+            return;
+        }
         let mut ed = self.rl.lock().unwrap();
         ed.add_history_entry(entry).ok();
     }
+
+    /// In private mode, the commands aren't added to the history
+    pub fn set_private(&mut self, private: bool) {
+        self.private = private;
+    }
 }
@@ -6,8 +6,13 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::cli::display::fg_color;
-use crate::cli::parser::{filter_high_ids, parse, parse_first, parse_id, ParseError, ParsedQuery};
+use crate::cli::display::{
+    fg_color,
+    theme::{Style, Theme},
+};
+use crate::cli::parser::{
+    filter_high_ids, parse_first, parse_id, parse_with, ParseError, ParsedQuery,
+};
 
 use termion::{color, style};
 
@@ -27,6 +32,8 @@ use rustyline::{
 pub enum Message {
     Over(rustyline::Result<ParsedQuery>),
     Unfinnished(ParsedQuery),
+    /// The terminal was resized
+    Resized,
 }
 
 #[derive(PartialEq, Eq)]
@@ -40,12 +47,24 @@ pub struct Helper {
     sender: Sender<Message>,
     high_limit: Option<usize>,
     use_color: bool,
+    prompt_style: Style,
+    hint_style: Style,
     stade: Stade,
+    customs: &'static [&'static str],
 }
 
 impl Helper {
-    pub fn new(use_color: bool) -> (Receiver<Message>, Sender<Message>, Helper) {
+    pub fn new(
+        use_color: bool,
+        theme: &Theme,
+        customs: &'static [&'static str],
+    ) -> (Receiver<Message>, Sender<Message>, Helper) {
         let (tx, rx) = channel();
+        let theme = if use_color {
+            theme.clone()
+        } else {
+            Theme::plain()
+        };
         (
             rx,
             tx.clone(),
@@ -53,7 +72,10 @@ impl Helper {
                 sender: tx,
                 high_limit: None,
                 use_color,
+                prompt_style: theme.prompt,
+                hint_style: theme.hint,
                 stade: Stade::First,
+                customs,
             },
         )
     }
@@ -74,7 +96,7 @@ impl Helper {
         let parsed = match self.stade {
             Stade::First => parse_first(line),
             Stade::IdOnly => parse_id(line),
-            Stade::Normal => parse(line),
+            Stade::Normal => parse_with(line, self.customs),
         };
 
         if let Some(max) = self.high_limit {
@@ -220,7 +242,11 @@ fn bold(line: &str) -> Cow<'_, str> {
 
 impl Highlighter for Helper {
     fn highlight_prompt<'b, 's: 'b, 'p: 'b>(&'s self, prompt: &'p str, _: bool) -> Cow<'b, str> {
-        Cow::Owned(format!("{}{}{}", style::Bold, prompt, style::Reset))
+        Cow::Owned(self.prompt_style.paint(prompt).to_string())
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(self.hint_style.paint(hint).to_string())
     }
 
     fn highlight<'l>(&self, line: &'l str, _: usize) -> Cow<'l, str> {
@@ -230,6 +256,35 @@ impl Highlighter for Helper {
             Ok(ParsedQuery::Info(_)) => green_then_bold(line, self.use_color),
             Ok(ParsedQuery::Comments(_)) => green_then_bold(line, self.use_color),
             Ok(ParsedQuery::Browser(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Download(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Cancel(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Retry(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Play(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::PlayWith(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Custom(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Queue(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::PlayQueue) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Autoplay(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Private(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Players) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Kill(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Downloads) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::History(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Forget(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::ClearHistory) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Stats) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Bookmark(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Block(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Unblock(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Blocklist) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Mute(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Unmute(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Mutes) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Report(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Bookmarks) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::PlaylistAdd(_, _)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::Playlist(_)) => green_then_bold(line, self.use_color),
+            Ok(ParsedQuery::PlaylistRm(_, _)) => green_then_bold(line, self.use_color),
             Ok(ParsedQuery::Query(_)) => bold(line),
             Ok(ParsedQuery::Id(_)) => green_then_bold(line, self.use_color),
             Ok(ParsedQuery::Quit) => green_then_bold(line, self.use_color),
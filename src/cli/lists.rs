@@ -0,0 +1,260 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use peertube_api::Video;
+use toml::value::{Array, Table, Value};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// Version of the format of the lists file
+const LISTS_VERSION: i64 = 1;
+
+/// Video saved in the bookmarks or in a playlist, with enough data to list it offline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedVideo {
+    pub uuid: String,
+    pub host: String,
+    pub title: String,
+    pub channel: String,
+}
+
+impl SavedVideo {
+    pub fn new(video: &Video) -> SavedVideo {
+        SavedVideo {
+            uuid: video.uuid().to_owned(),
+            host: video.host().to_owned(),
+            title: video.name().to_owned(),
+            channel: video.channel_display().to_owned(),
+        }
+    }
+
+    fn to_toml(&self) -> Value {
+        let mut t = Table::new();
+        t.insert("uuid".into(), Value::String(self.uuid.clone()));
+        t.insert("host".into(), Value::String(self.host.clone()));
+        t.insert("title".into(), Value::String(self.title.clone()));
+        t.insert("channel".into(), Value::String(self.channel.clone()));
+        Value::Table(t)
+    }
+
+    fn from_toml(v: &Value) -> Option<SavedVideo> {
+        let string = |name| v.get(name).and_then(Value::as_str).unwrap_or("").to_owned();
+        Some(SavedVideo {
+            uuid: v.get("uuid")?.as_str()?.to_owned(),
+            host: string("host"),
+            title: string("title"),
+            channel: string("channel"),
+        })
+    }
+}
+
+/// Bookmarks and named playlists, saved locally
+pub struct Lists {
+    bookmarks: Vec<SavedVideo>,
+    playlists: BTreeMap<String, Vec<SavedVideo>>,
+
+    /// Set when the file comes from a newer version or can't be read, so that it isn't overwritten
+    read_only: bool,
+}
+
+impl Lists {
+    pub fn new() -> Lists {
+        Lists {
+            bookmarks: Vec::new(),
+            playlists: BTreeMap::new(),
+            read_only: false,
+        }
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<(), Error> {
+        let table = match fs::read_to_string(path).and_then(|content| {
+            content
+                .parse::<Table>()
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))
+        }) {
+            Ok(table) => table,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Err(err),
+            // Saving would replace the lists that couldn't be loaded
+            Err(err) => {
+                self.read_only = true;
+                return Err(err);
+            }
+        };
+        let version = table.get("version").and_then(Value::as_integer);
+        if version != Some(LISTS_VERSION) {
+            self.read_only = true;
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Unsupported lists version: {version:?}"),
+            ));
+        }
+
+        self.bookmarks = videos_from_toml(table.get("bookmarks"));
+        if let Some(Value::Table(playlists)) = table.get("playlists") {
+            for (name, videos) in playlists {
+                self.playlists
+                    .insert(name.clone(), videos_from_toml(Some(videos)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if self.read_only {
+            return Ok(());
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    fn to_toml(&self) -> String {
+        let mut playlists = Table::new();
+        for (name, videos) in &self.playlists {
+            playlists.insert(name.clone(), videos_to_toml(videos));
+        }
+        let mut table = Table::new();
+        table.insert("version".into(), Value::Integer(LISTS_VERSION));
+        table.insert("bookmarks".into(), videos_to_toml(&self.bookmarks));
+        table.insert("playlists".into(), Value::Table(playlists));
+        table.to_string()
+    }
+
+    pub fn bookmarks(&self) -> &[SavedVideo] {
+        &self.bookmarks
+    }
+
+    /// Bookmark the video, or remove it from the bookmarks if it already was
+    ///
+    /// Returns whether the video is bookmarked
+    pub fn toggle_bookmark(&mut self, video: SavedVideo) -> bool {
+        match self.bookmarks.iter().position(|v| v.uuid == video.uuid) {
+            Some(i) => {
+                self.bookmarks.remove(i);
+                false
+            }
+            None => {
+                self.bookmarks.push(video);
+                true
+            }
+        }
+    }
+
+    pub fn playlist(&self, name: &str) -> Option<&[SavedVideo]> {
+        self.playlists.get(name).map(Vec::as_slice)
+    }
+
+    /// Names of the playlists with their number of videos
+    pub fn playlists(&self) -> Vec<(&str, usize)> {
+        self.playlists
+            .iter()
+            .map(|(name, videos)| (name.as_str(), videos.len()))
+            .collect()
+    }
+
+    /// Add a video at the end of a playlist, creating it if needed
+    ///
+    /// Returns false if the video was already in the playlist
+    pub fn add_to_playlist(&mut self, name: &str, video: SavedVideo) -> bool {
+        let videos = self.playlists.entry(name.to_owned()).or_default();
+        if videos.iter().any(|v| v.uuid == video.uuid) {
+            false
+        } else {
+            videos.push(video);
+            true
+        }
+    }
+
+    /// Returns false if the video wasn't in the playlist
+    pub fn remove_from_playlist(&mut self, name: &str, uuid: &str) -> bool {
+        match self.playlists.get_mut(name) {
+            Some(videos) => {
+                let len = videos.len();
+                videos.retain(|v| v.uuid != uuid);
+                videos.len() != len
+            }
+            None => false,
+        }
+    }
+
+    /// Returns false if the playlist doesn't exist
+    pub fn remove_playlist(&mut self, name: &str) -> bool {
+        self.playlists.remove(name).is_some()
+    }
+}
+
+fn videos_to_toml(videos: &[SavedVideo]) -> Value {
+    Value::Array(videos.iter().map(SavedVideo::to_toml).collect::<Array>())
+}
+
+fn videos_from_toml(value: Option<&Value>) -> Vec<SavedVideo> {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(SavedVideo::from_toml)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn video(uuid: &str) -> SavedVideo {
+        SavedVideo {
+            uuid: uuid.into(),
+            host: "framatube.org".into(),
+            title: format!("Video {uuid}"),
+            channel: "Framasoft".into(),
+        }
+    }
+
+    #[test]
+    fn lists() {
+        let mut lists = Lists::new();
+        assert!(lists.toggle_bookmark(video("a")));
+        assert!(lists.toggle_bookmark(video("b")));
+        assert!(!lists.toggle_bookmark(video("a")));
+        assert_eq!(lists.bookmarks(), &[video("b")]);
+
+        assert!(lists.add_to_playlist("talks", video("a")));
+        assert!(lists.add_to_playlist("talks", video("c")));
+        assert!(!lists.add_to_playlist("talks", video("a")));
+        assert!(lists.add_to_playlist("music", video("b")));
+        assert_eq!(lists.playlists(), vec![("music", 1), ("talks", 2)]);
+        assert!(lists.remove_from_playlist("talks", "a"));
+        assert!(!lists.remove_from_playlist("talks", "a"));
+        assert!(!lists.remove_from_playlist("unknown", "a"));
+        assert_eq!(lists.playlist("talks"), Some(&[video("c")][..]));
+
+        let path = std::env::temp_dir().join(format!("ptv-lists-test-{}", std::process::id()));
+        lists.save(&path).unwrap();
+        let mut reloaded = Lists::new();
+        reloaded.load_file(&path).unwrap();
+        assert_eq!(reloaded.bookmarks(), lists.bookmarks());
+
+        // A file that can't be parsed isn't overwritten
+        fs::write(&path, "version = 1\nbookmarks = [").unwrap();
+        let mut broken = Lists::new();
+        assert!(broken.load_file(&path).is_err());
+        broken.toggle_bookmark(video("a"));
+        broken.save(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "version = 1\nbookmarks = ["
+        );
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded.playlists(), lists.playlists());
+        assert_eq!(reloaded.playlist("music"), Some(&[video("b")][..]));
+
+        assert!(reloaded.remove_playlist("music"));
+        assert!(!reloaded.remove_playlist("music"));
+    }
+}
@@ -0,0 +1,159 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use serde_json::Value;
+use std::env::temp_dir;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child};
+
+/// State of the playback when the player exited
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Last known position in seconds
+    pub position: Option<f64>,
+    pub duration: Option<f64>,
+    /// Whether the video was played until the end
+    pub eof: bool,
+}
+
+impl Progress {
+    /// Share of the video that was watched
+    pub fn watched(&self, fallback_duration: u64) -> f64 {
+        if self.eof {
+            return 1.;
+        }
+        let duration = self.duration.unwrap_or(fallback_duration as f64);
+        match self.position {
+            Some(pos) if duration > 0. => pos / duration,
+            _ => 0.,
+        }
+    }
+
+    /// Update the progress with a message received from mpv
+    fn update(&mut self, line: &str) {
+        let Ok(msg) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        match msg["event"].as_str() {
+            Some("property-change") => match msg["name"].as_str() {
+                Some("time-pos") => {
+                    if let Some(pos) = msg["data"].as_f64() {
+                        self.position = Some(pos);
+                    }
+                }
+                Some("duration") => {
+                    if let Some(d) = msg["data"].as_f64() {
+                        self.duration = Some(d);
+                    }
+                }
+                _ => {}
+            },
+            Some("end-file") if msg["reason"] == "eof" => self.eof = true,
+            _ => {}
+        }
+    }
+}
+
+/// The playback is followed through a unix socket, elsewhere the videos are handled as without IPC
+pub const IPC_SUPPORTED: bool = cfg!(unix);
+
+/// Whether the player command is mpv
+pub fn is_mpv(player: &str) -> bool {
+    Path::new(player).file_stem().and_then(|s| s.to_str()) == Some("mpv")
+}
+
+/// Path of the socket used to communicate with the player
+pub fn socket_path() -> PathBuf {
+    temp_dir().join(format!("peertube-viewer-rs-{}.sock", process::id()))
+}
+
+/// Wait for the player to exit while following its playback
+/// through its [JSON IPC](https://mpv.io/manual/stable/#json-ipc)
+#[cfg(unix)]
+pub fn follow(child: &mut Child, socket: &Path) -> io::Result<Progress> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    let mut progress = Progress::default();
+
+    // The socket is only created once mpv is started
+    let mut stream = None;
+    for _ in 0..100 {
+        if child.try_wait()?.is_some() {
+            break;
+        }
+        if let Ok(s) = UnixStream::connect(socket) {
+            stream = Some(s);
+            break;
+        }
+        sleep(Duration::from_millis(100));
+    }
+
+    if let Some(mut stream) = stream {
+        stream.write_all(
+            b"{\"command\": [\"observe_property\", 1, \"time-pos\"]}\n\
+              {\"command\": [\"observe_property\", 2, \"duration\"]}\n",
+        )?;
+        for line in BufReader::new(stream).lines() {
+            match line {
+                Ok(line) => progress.update(&line),
+                Err(_) => break,
+            }
+        }
+    }
+
+    child.wait()?;
+    std::fs::remove_file(socket).unwrap_or(());
+    Ok(progress)
+}
+
+/// Never called since `IPC_SUPPORTED` is false
+#[cfg(not(unix))]
+pub fn follow(child: &mut Child, _: &Path) -> io::Result<Progress> {
+    child.wait()?;
+    Ok(Progress::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn events() {
+        let mut progress = Progress::default();
+        progress.update(r#"{"event":"property-change","id":1,"name":"time-pos","data":83.5}"#);
+        progress.update(r#"{"event":"property-change","id":2,"name":"duration","data":167.0}"#);
+        progress.update(r#"{"data":null,"request_id":0,"error":"success"}"#);
+        progress.update("not json");
+        assert_eq!(
+            progress,
+            Progress {
+                position: Some(83.5),
+                duration: Some(167.),
+                eof: false,
+            }
+        );
+        assert_eq!(progress.watched(0), 0.5);
+
+        progress.update(r#"{"event":"end-file","reason":"quit"}"#);
+        assert!(!progress.eof);
+        progress.update(r#"{"event":"end-file","reason":"eof"}"#);
+        assert_eq!(progress.watched(0), 1.);
+    }
+
+    #[test]
+    fn player() {
+        assert!(is_mpv("mpv"));
+        assert!(is_mpv("/usr/bin/mpv"));
+        assert!(!is_mpv("vlc"));
+    }
+}
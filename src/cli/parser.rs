@@ -6,38 +6,94 @@
 //
 // You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
 
-const COMMANDS: [&str; 14] = [
+use super::config::BlockKind;
+use super::resolution::parse_resolution;
+
+const COMMANDS: [&str; 45] = [
     //Sorted list of available commands
+    ":autoplay",
+    ":block-account",
+    ":block-channel",
+    ":block-instance",
+    ":blocklist",
+    ":bookmark",
+    ":bookmarks",
     ":browser",
+    ":cancel",
     ":chandle",
     ":channels",
+    ":clear-history",
     ":comments",
+    ":download",
+    ":downloads",
+    ":forget",
     ":h",
     ":help",
+    ":history",
     ":info",
+    ":kill",
+    ":mute-account",
+    ":mute-instance",
+    ":mutes",
     ":n",
     ":next",
     ":p",
+    ":play",
+    ":play-queue",
+    ":play-with",
+    ":players",
+    ":playlist",
+    ":playlist-add",
+    ":playlist-rm",
     ":previous",
+    ":private",
     ":q",
+    ":queue",
     ":quit",
+    ":report",
+    ":retry",
+    ":stats",
     ":trending",
+    ":unblock",
+    ":unmute",
 ];
 
-const COMMANDS_FIRST: [&str; 7] = [
+const COMMANDS_FIRST: [&str; 13] = [
     //Sorted list of available commands
+    ":bookmarks",
     ":chandle",
     ":channels",
+    ":downloads",
     ":h",
     ":help",
+    ":history",
+    ":playlist",
+    ":private",
     ":q",
     ":quit",
+    ":stats",
     ":trending",
 ];
 
-const NO_ARGS_FIRST_CMDS_WITH_SPACE: [&str; 5] = [":h ", ":help ", ":q ", ":quit ", ":trending "];
+const NO_ARGS_FIRST_CMDS_WITH_SPACE: [&str; 13] = [
+    ":blocklist ",
+    ":bookmarks ",
+    ":clear-history ",
+    ":downloads ",
+    ":h ",
+    ":help ",
+    ":mutes ",
+    ":play-queue ",
+    ":players ",
+    ":q ",
+    ":quit ",
+    ":stats ",
+    ":trending ",
+];
 
-const NO_ARGS_CMDS_WITH_SPACE: [&str; 9] = [
+const NO_ARGS_CMDS_WITH_SPACE: [&str; 12] = [
+    ":bookmarks ",
+    ":downloads ",
     ":h ",
     ":help ",
     ":n ",
@@ -46,6 +102,7 @@ const NO_ARGS_CMDS_WITH_SPACE: [&str; 9] = [
     ":previous ",
     ":q ",
     ":quit ",
+    ":stats ",
     ":trending ",
 ];
 
@@ -56,8 +113,51 @@ pub enum ParsedQuery {
     Info(usize),
     Comments(usize),
     Browser(usize),
+    Download(Vec<usize>),
+    Cancel(usize),
+    Retry(usize),
+    Play(usize, Option<u64>),
+    /// Play a video with a player profile
+    PlayWith(String, usize),
+    /// Command defined in the config
+    Custom(String, usize),
+    /// Add videos to the queue, or show it when empty
+    Queue(Vec<usize>),
+    Autoplay(bool),
+    /// Stop or resume recording the history
+    Private(bool),
+    Kill(usize),
+    /// Browse the history, only showing the videos matching the query when it isn't empty
+    History(String),
+    Forget(usize),
+    Bookmark(usize),
+    /// Block the instance, channel or account of an item
+    Block(BlockKind, usize),
+    /// Remove an instance, a handle or a keyword from the blocklist
+    Unblock(String),
+    /// Add a video to a local playlist
+    PlaylistAdd(String, usize),
+    /// Open a local playlist, or list them when the name is empty
+    Playlist(String),
+    /// Remove a video from a local playlist, or the whole playlist without id
+    PlaylistRm(String, Option<usize>),
     Query(String),
     Id(usize),
+    Downloads,
+    Blocklist,
+    /// Mute the instance or the account of an item for the logged in user
+    Mute(BlockKind, usize),
+    /// Unmute an instance or an account handle
+    Unmute(String),
+    /// Show the instances and accounts muted by the logged in user
+    Mutes,
+    /// Report a video or a comment with the given reason
+    Report(usize, String),
+    Bookmarks,
+    PlayQueue,
+    Players,
+    ClearHistory,
+    Stats,
     Help,
     Quit,
     Next,
@@ -68,7 +168,11 @@ pub enum ParsedQuery {
 impl ParsedQuery {
     pub fn should_preload(&self) -> Option<usize> {
         match *self {
-            ParsedQuery::Info(id) | ParsedQuery::Comments(id) | ParsedQuery::Id(id) => Some(id),
+            ParsedQuery::Info(id)
+            | ParsedQuery::Comments(id)
+            | ParsedQuery::Id(id)
+            | ParsedQuery::Play(id, _)
+            | ParsedQuery::PlayWith(_, id) => Some(id),
             _ => None,
         }
     }
@@ -96,14 +200,42 @@ pub fn filter_high_ids(
         Ok(ParsedQuery::Info(id))
         | Ok(ParsedQuery::Comments(id))
         | Ok(ParsedQuery::Browser(id))
+        | Ok(ParsedQuery::Play(id, _))
+        | Ok(ParsedQuery::PlayWith(_, id))
+        | Ok(ParsedQuery::Custom(_, id))
+        | Ok(ParsedQuery::Forget(id))
+        | Ok(ParsedQuery::Bookmark(id))
+        | Ok(ParsedQuery::Block(_, id))
+        | Ok(ParsedQuery::Mute(_, id))
+        | Ok(ParsedQuery::Report(id, _))
+        | Ok(ParsedQuery::PlaylistAdd(_, id))
+        | Ok(ParsedQuery::PlaylistRm(_, Some(id)))
             if *id >= max =>
         {
             Err(ParseError::ArgTooHigh)
         }
+        Ok(ParsedQuery::Download(ids)) | Ok(ParsedQuery::Queue(ids))
+            if ids.iter().any(|id| *id >= max) =>
+        {
+            Err(ParseError::ArgTooHigh)
+        }
+        Ok(ParsedQuery::Download(ids)) | Ok(ParsedQuery::Queue(ids)) if ids.contains(&0) => {
+            Err(ParseError::IdZero)
+        }
         Ok(ParsedQuery::Id(id)) if *id >= max => Err(ParseError::IdTooHigh),
         Ok(ParsedQuery::Info(id))
         | Ok(ParsedQuery::Comments(id))
         | Ok(ParsedQuery::Browser(id))
+        | Ok(ParsedQuery::Play(id, _))
+        | Ok(ParsedQuery::PlayWith(_, id))
+        | Ok(ParsedQuery::Custom(_, id))
+        | Ok(ParsedQuery::Forget(id))
+        | Ok(ParsedQuery::Bookmark(id))
+        | Ok(ParsedQuery::Block(_, id))
+        | Ok(ParsedQuery::Mute(_, id))
+        | Ok(ParsedQuery::Report(id, _))
+        | Ok(ParsedQuery::PlaylistAdd(_, id))
+        | Ok(ParsedQuery::PlaylistRm(_, Some(id)))
         | Ok(ParsedQuery::Id(id))
             if *id == 0 =>
         {
@@ -113,7 +245,18 @@ pub fn filter_high_ids(
     }
 }
 
+/// Whether the command is one of the commands available during a session
+pub fn is_builtin(cmd: &str) -> bool {
+    COMMANDS.binary_search(&cmd).is_ok()
+}
+
+#[cfg(test)]
 pub fn parse(input: &str) -> Result<ParsedQuery, ParseError> {
+    parse_with(input, &[])
+}
+
+/// Parse the input with the commands defined in the config (ex: `:kodi`) in addition to the others
+pub fn parse_with(input: &str, customs: &[&'static str]) -> Result<ParsedQuery, ParseError> {
     if input.is_empty() {
         return Err(ParseError::Empty);
     }
@@ -169,6 +312,199 @@ pub fn parse(input: &str) -> Result<ParsedQuery, ParseError> {
                 .parse()
                 .map_err(|_| ParseError::BadArgType)?,
         ))
+    } else if input.starts_with(":download ") || input == ":download" {
+        Ok(ParsedQuery::Download(parse_ids(
+            input
+                .get(9..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?,
+        )?))
+    } else if input.starts_with(":play ") || input == ":play" {
+        parse_play(
+            input
+                .get(5..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?,
+        )
+    } else if input.starts_with(":play-with ") || input == ":play-with" {
+        let mut parts = input[10..].split(' ').filter(|p| !p.is_empty());
+        let profile = parts.next().ok_or(ParseError::MissingArgs)?;
+        let id = parts
+            .next()
+            .ok_or(ParseError::MissingArgs)?
+            .parse()
+            .map_err(|_| ParseError::BadArgType)?;
+        if parts.next().is_some() {
+            return Err(ParseError::UnexpectedArgs);
+        }
+        Ok(ParsedQuery::PlayWith(profile.to_string(), id))
+    } else if input == ":play-queue" {
+        Ok(ParsedQuery::PlayQueue)
+    } else if input == ":players" {
+        Ok(ParsedQuery::Players)
+    } else if input.starts_with(":kill ") || input == ":kill" {
+        Ok(ParsedQuery::Kill(
+            input
+                .get(5..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input.starts_with(":queue ") || input == ":queue" {
+        Ok(ParsedQuery::Queue(
+            match input.get(6..).and_then(clean_spaces) {
+                Some(ids) => parse_ids(ids)?,
+                None => Vec::new(),
+            },
+        ))
+    } else if input.starts_with(":autoplay ") || input == ":autoplay" {
+        match input.get(9..).and_then(clean_spaces) {
+            Some("on") => Ok(ParsedQuery::Autoplay(true)),
+            Some("off") => Ok(ParsedQuery::Autoplay(false)),
+            Some(_) => Err(ParseError::BadArgType),
+            None => Err(ParseError::MissingArgs),
+        }
+    } else if input.starts_with(":private ") || input == ":private" {
+        parse_private(input)
+    } else if input.starts_with(":history ") || input == ":history" {
+        Ok(ParsedQuery::History(
+            input
+                .get(8..)
+                .and_then(clean_spaces)
+                .unwrap_or("")
+                .to_string(),
+        ))
+    } else if input.starts_with(":forget ") || input == ":forget" {
+        Ok(ParsedQuery::Forget(
+            input
+                .get(7..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input == ":clear-history" {
+        Ok(ParsedQuery::ClearHistory)
+    } else if input.starts_with(":bookmark ") || input == ":bookmark" {
+        Ok(ParsedQuery::Bookmark(
+            input
+                .get(9..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input == ":bookmarks" {
+        Ok(ParsedQuery::Bookmarks)
+    } else if let Some((kind, args)) = [
+        (BlockKind::Instance, ":block-instance"),
+        (BlockKind::Channel, ":block-channel"),
+        (BlockKind::Account, ":block-account"),
+    ]
+    .into_iter()
+    .find_map(|(kind, cmd)| Some((kind, input.strip_prefix(cmd)?)))
+    .filter(|(_, args)| args.is_empty() || args.starts_with(' '))
+    {
+        Ok(ParsedQuery::Block(
+            kind,
+            clean_spaces(args)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input.starts_with(":unblock ") || input == ":unblock" {
+        Ok(ParsedQuery::Unblock(
+            input
+                .get(8..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .to_string(),
+        ))
+    } else if input == ":blocklist" {
+        Ok(ParsedQuery::Blocklist)
+    } else if let Some((kind, args)) = [
+        (BlockKind::Instance, ":mute-instance"),
+        (BlockKind::Account, ":mute-account"),
+    ]
+    .into_iter()
+    .find_map(|(kind, cmd)| Some((kind, input.strip_prefix(cmd)?)))
+    .filter(|(_, args)| args.is_empty() || args.starts_with(' '))
+    {
+        Ok(ParsedQuery::Mute(
+            kind,
+            clean_spaces(args)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input.starts_with(":unmute ") || input == ":unmute" {
+        Ok(ParsedQuery::Unmute(
+            input
+                .get(7..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .to_string(),
+        ))
+    } else if input == ":mutes" {
+        Ok(ParsedQuery::Mutes)
+    } else if input.starts_with(":report ") || input == ":report" {
+        let (id, reason) = input
+            .get(8..)
+            .and_then(clean_spaces)
+            .and_then(|args| args.split_once(' '))
+            .ok_or(ParseError::MissingArgs)?;
+        Ok(ParsedQuery::Report(
+            id.parse().map_err(|_| ParseError::BadArgType)?,
+            reason.trim_start().to_string(),
+        ))
+    } else if input.starts_with(":playlist ") || input == ":playlist" {
+        parse_playlist(input)
+    } else if input.starts_with(":playlist-add ") || input == ":playlist-add" {
+        let mut parts = input[13..].split(' ').filter(|p| !p.is_empty());
+        let name = parts.next().ok_or(ParseError::MissingArgs)?;
+        let id = parts
+            .next()
+            .ok_or(ParseError::MissingArgs)?
+            .parse()
+            .map_err(|_| ParseError::BadArgType)?;
+        if parts.next().is_some() {
+            return Err(ParseError::UnexpectedArgs);
+        }
+        Ok(ParsedQuery::PlaylistAdd(name.to_string(), id))
+    } else if input.starts_with(":playlist-rm ") || input == ":playlist-rm" {
+        let mut parts = input[12..].split(' ').filter(|p| !p.is_empty());
+        let name = parts.next().ok_or(ParseError::MissingArgs)?;
+        let id = parts
+            .next()
+            .map(|id| id.parse().map_err(|_| ParseError::BadArgType))
+            .transpose()?;
+        if parts.next().is_some() {
+            return Err(ParseError::UnexpectedArgs);
+        }
+        Ok(ParsedQuery::PlaylistRm(name.to_string(), id))
+    } else if input.starts_with(":cancel ") || input == ":cancel" {
+        Ok(ParsedQuery::Cancel(
+            input
+                .get(7..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input.starts_with(":retry ") || input == ":retry" {
+        Ok(ParsedQuery::Retry(
+            input
+                .get(6..)
+                .and_then(clean_spaces)
+                .ok_or(ParseError::MissingArgs)?
+                .parse()
+                .map_err(|_| ParseError::BadArgType)?,
+        ))
+    } else if input == ":downloads" {
+        Ok(ParsedQuery::Downloads)
+    } else if input == ":stats" {
+        Ok(ParsedQuery::Stats)
     } else if input == ":trending" {
         Ok(ParsedQuery::Trending)
     } else if input == ":h" || input == ":help" {
@@ -180,18 +516,35 @@ pub fn parse(input: &str) -> Result<ParsedQuery, ParseError> {
     } else if input == ":n" || input == ":next" {
         Ok(ParsedQuery::Next)
     } else {
+        for cmd in customs {
+            if let Some(args) = input.strip_prefix(cmd) {
+                if args.is_empty() || args.starts_with(' ') {
+                    return Ok(ParsedQuery::Custom(
+                        cmd[1..].to_string(),
+                        clean_spaces(args)
+                            .ok_or(ParseError::MissingArgs)?
+                            .parse()
+                            .map_err(|_| ParseError::BadArgType)?,
+                    ));
+                }
+            }
+        }
+
         for cmd in &NO_ARGS_FIRST_CMDS_WITH_SPACE {
             if input.starts_with(cmd) {
                 return Err(ParseError::UnexpectedArgs);
             }
         }
 
-        let starts = COMMANDS.iter().fold(Vec::new(), |mut acc, cmd| {
-            if cmd.starts_with(input) {
-                acc.push(*cmd);
-            }
-            acc
-        });
+        let starts = COMMANDS
+            .iter()
+            .chain(customs)
+            .fold(Vec::new(), |mut acc, cmd| {
+                if cmd.starts_with(input) {
+                    acc.push(*cmd);
+                }
+                acc
+            });
 
         if !starts.is_empty() {
             Err(ParseError::IncompleteCommand(starts))
@@ -223,6 +576,24 @@ pub fn parse_first(input: &str) -> Result<ParsedQuery, ParseError> {
                 .ok_or(ParseError::MissingArgs)?
                 .to_string(),
         ))
+    } else if input == ":downloads" {
+        Ok(ParsedQuery::Downloads)
+    } else if input == ":bookmarks" {
+        Ok(ParsedQuery::Bookmarks)
+    } else if input.starts_with(":playlist ") || input == ":playlist" {
+        parse_playlist(input)
+    } else if input.starts_with(":history ") || input == ":history" {
+        Ok(ParsedQuery::History(
+            input
+                .get(8..)
+                .and_then(clean_spaces)
+                .unwrap_or("")
+                .to_string(),
+        ))
+    } else if input.starts_with(":private ") || input == ":private" {
+        parse_private(input)
+    } else if input == ":stats" {
+        Ok(ParsedQuery::Stats)
     } else if input == ":trending" {
         Ok(ParsedQuery::Trending)
     } else if input == ":h" || input == ":help" {
@@ -262,6 +633,84 @@ pub fn parse_id(input: &str) -> Result<ParsedQuery, ParseError> {
     Err(ParseError::ExpectId)
 }
 
+/// Parse an id optionally followed by a resolution (ex: `3 480p`)
+fn parse_play(input: &str) -> Result<ParsedQuery, ParseError> {
+    let mut parts = input.split(' ').filter(|p| !p.is_empty());
+    let id = parts
+        .next()
+        .ok_or(ParseError::MissingArgs)?
+        .parse()
+        .map_err(|_| ParseError::BadArgType)?;
+    let resolution = parts
+        .next()
+        .map(|r| parse_resolution(r).ok_or(ParseError::BadArgType))
+        .transpose()?;
+    if parts.next().is_some() {
+        return Err(ParseError::UnexpectedArgs);
+    }
+    Ok(ParsedQuery::Play(id, resolution))
+}
+
+/// Parse `:private on|off`
+fn parse_private(input: &str) -> Result<ParsedQuery, ParseError> {
+    match input.get(8..).and_then(clean_spaces) {
+        Some("on") => Ok(ParsedQuery::Private(true)),
+        Some("off") => Ok(ParsedQuery::Private(false)),
+        Some(_) => Err(ParseError::BadArgType),
+        None => Err(ParseError::MissingArgs),
+    }
+}
+
+/// Parse `:playlist` with an optional playlist name
+fn parse_playlist(input: &str) -> Result<ParsedQuery, ParseError> {
+    let mut parts = input[9..].split(' ').filter(|p| !p.is_empty());
+    let name = parts.next().unwrap_or("");
+    if parts.next().is_some() {
+        return Err(ParseError::UnexpectedArgs);
+    }
+    Ok(ParsedQuery::Playlist(name.to_string()))
+}
+
+/// Highest id accepted in a list of ids, checked before expanding the ranges
+const MAX_LIST_ID: usize = 1000;
+
+/// Parse a list of ids separated by spaces, with support for ranges (ex: `1 3 5-8`)
+pub fn parse_ids(input: &str) -> Result<Vec<usize>, ParseError> {
+    let mut ids = Vec::new();
+    for part in input.split(' ').filter(|p| !p.is_empty()) {
+        if let Some((start, end)) = part.split_once('-') {
+            let start: usize = start.parse().map_err(|_| ParseError::BadArgType)?;
+            let end: usize = end.parse().map_err(|_| ParseError::BadArgType)?;
+            if start > end {
+                return Err(ParseError::BadArgType);
+            }
+            if end > MAX_LIST_ID {
+                return Err(ParseError::ArgTooHigh);
+            }
+            ids.extend(start..=end);
+        } else {
+            ids.push(part.parse().map_err(|_| ParseError::BadArgType)?);
+        }
+    }
+    Ok(ids)
+}
+
+/// Parse a timestamp as seconds, minutes and seconds or hours, minutes and seconds (ex: `1:05:30`)
+fn parse_timestamp(input: &str) -> Option<u64> {
+    input
+        .split(':')
+        .try_fold(0, |acc, part| Some(acc * 60 + part.parse::<u64>().ok()?))
+}
+
+/// Parse a range of timestamps (ex: `1:20-2:05`), returns the start and end in seconds
+pub fn parse_time_range(input: &str) -> Result<(u64, u64), ParseError> {
+    let (start, end) = input.split_once('-').ok_or(ParseError::BadArgType)?;
+    match (parse_timestamp(start.trim()), parse_timestamp(end.trim())) {
+        (Some(start), Some(end)) if start <= end => Ok((start, end)),
+        _ => Err(ParseError::BadArgType),
+    }
+}
+
 pub fn clean_spaces(input: &str) -> Option<&str> {
     let mut start: usize = 0;
     let mut chars = input.chars();
@@ -321,6 +770,142 @@ mod tests {
         assert_eq!(parse(":p"), Ok(Previous));
         assert_eq!(parse("12"), Ok(Id(12)));
         assert_eq!(parse("110"), Ok(Id(110)));
+        assert_eq!(parse(":downloads"), Ok(Downloads));
+        assert_eq!(parse(":downloads 1"), Err(UnexpectedArgs));
+        assert_eq!(parse(":download"), Err(MissingArgs));
+        assert_eq!(parse(":download 3"), Ok(Download(vec![3])));
+        assert_eq!(parse(":cancel 2"), Ok(Cancel(2)));
+        assert_eq!(parse(":retry 2"), Ok(Retry(2)));
+        assert_eq!(parse(":retry"), Err(MissingArgs));
+        assert_eq!(parse(":play 3"), Ok(Play(3, None)));
+        assert_eq!(parse(":play 3 480p"), Ok(Play(3, Some(480))));
+        assert_eq!(parse(":play 3 720"), Ok(Play(3, Some(720))));
+        assert_eq!(parse(":play 3 hd"), Err(BadArgType));
+        assert_eq!(parse(":play 3 480p 2"), Err(UnexpectedArgs));
+        assert_eq!(parse(":play"), Err(MissingArgs));
+        assert_eq!(parse(":play-with tv 2"), Ok(PlayWith("tv".to_string(), 2)));
+        assert_eq!(parse(":play-with tv"), Err(MissingArgs));
+        assert_eq!(parse(":play-with 2 tv"), Err(BadArgType));
+        assert_eq!(parse(":play-queue"), Ok(PlayQueue));
+        assert_eq!(parse(":play-queue 2"), Err(UnexpectedArgs));
+        assert_eq!(parse(":history"), Ok(History(String::new())));
+        assert_eq!(parse(":history  rust "), Ok(History("rust".to_string())));
+        assert_eq!(
+            parse_first(":history rust"),
+            Ok(History("rust".to_string()))
+        );
+        assert_eq!(parse(":forget 2"), Ok(Forget(2)));
+        assert_eq!(parse(":forget"), Err(MissingArgs));
+        assert_eq!(filter_high_ids(parse(":forget 0"), 5), Err(IdZero));
+        assert_eq!(parse(":clear-history"), Ok(ClearHistory));
+        assert_eq!(parse(":stats"), Ok(Stats));
+        assert_eq!(parse_first(":stats"), Ok(Stats));
+        assert_eq!(parse_first(":stats 1"), Err(UnexpectedArgs));
+        assert_eq!(parse(":clear-history 1"), Err(UnexpectedArgs));
+        assert_eq!(parse(":queue"), Ok(Queue(Vec::new())));
+        assert_eq!(parse(":queue 1 4-5"), Ok(Queue(vec![1, 4, 5])));
+        assert_eq!(filter_high_ids(parse(":queue 1 9"), 5), Err(ArgTooHigh));
+        assert_eq!(parse(":autoplay on"), Ok(Autoplay(true)));
+        assert_eq!(parse(":autoplay off"), Ok(Autoplay(false)));
+        assert_eq!(parse(":autoplay yes"), Err(BadArgType));
+        assert_eq!(parse(":autoplay"), Err(MissingArgs));
+        assert_eq!(parse(":private on"), Ok(Private(true)));
+        assert_eq!(parse_first(":private off"), Ok(Private(false)));
+        assert_eq!(parse(":private"), Err(MissingArgs));
+        assert_eq!(parse(":players"), Ok(Players));
+        assert_eq!(parse(":kill 1"), Ok(Kill(1)));
+        assert_eq!(parse(":kill"), Err(MissingArgs));
+        assert_eq!(parse(":bookmark 2"), Ok(Bookmark(2)));
+        assert_eq!(parse(":bookmark"), Err(MissingArgs));
+        assert_eq!(parse(":bookmarks"), Ok(Bookmarks));
+        assert_eq!(parse_first(":bookmarks 2"), Err(UnexpectedArgs));
+        assert_eq!(parse(":playlist"), Ok(Playlist(String::new())));
+        assert_eq!(
+            parse_first(":playlist talks"),
+            Ok(Playlist("talks".to_string()))
+        );
+        assert_eq!(parse(":playlist talks 2"), Err(UnexpectedArgs));
+        assert_eq!(
+            parse(":playlist-add talks 3"),
+            Ok(PlaylistAdd("talks".to_string(), 3))
+        );
+        assert_eq!(parse(":playlist-add talks"), Err(MissingArgs));
+        assert_eq!(
+            filter_high_ids(parse(":playlist-add talks 9"), 5),
+            Err(ArgTooHigh)
+        );
+        assert_eq!(
+            parse(":playlist-rm talks 1"),
+            Ok(PlaylistRm("talks".to_string(), Some(1)))
+        );
+        assert_eq!(
+            parse(":playlist-rm talks"),
+            Ok(PlaylistRm("talks".to_string(), None))
+        );
+        assert_eq!(parse(":playlist-rm"), Err(MissingArgs));
+        assert_eq!(parse(":block-channel 3"), Ok(Block(BlockKind::Channel, 3)));
+        assert_eq!(parse(":block-instance"), Err(MissingArgs));
+        assert_eq!(filter_high_ids(parse(":block-account 0"), 5), Err(IdZero));
+        assert_eq!(
+            parse(":unblock (?i)free v-bucks"),
+            Ok(Unblock("(?i)free v-bucks".to_string()))
+        );
+        assert_eq!(parse(":blocklist"), Ok(Blocklist));
+        assert_eq!(parse(":blocklist 1"), Err(UnexpectedArgs));
+        assert_eq!(parse(":mute-account 2"), Ok(Mute(BlockKind::Account, 2)));
+        assert_eq!(
+            parse(":unmute spammer@tube.example.com"),
+            Ok(Unmute("spammer@tube.example.com".to_string()))
+        );
+        assert_eq!(parse(":mutes"), Ok(Mutes));
+        assert_eq!(
+            parse(":report 2 Spam  link"),
+            Ok(Report(2, "Spam  link".to_string()))
+        );
+        assert_eq!(parse(":report 2"), Err(MissingArgs));
+        assert_eq!(parse_time_range("1:20-1:02:05"), Ok((80, 3725)));
+        assert_eq!(parse_time_range("30 - 45"), Ok((30, 45)));
+        assert_eq!(parse_time_range("2:00-1:00"), Err(BadArgType));
+        assert_eq!(parse_time_range("1:2a-3"), Err(BadArgType));
+    }
+
+    #[test]
+    fn custom() {
+        let customs = [":kodi", ":wiki"];
+        assert_eq!(
+            parse_with(":kodi 3", &customs),
+            Ok(Custom("kodi".to_string(), 3))
+        );
+        assert_eq!(parse_with(":kodi", &customs), Err(MissingArgs));
+        assert_eq!(parse_with(":kodi a", &customs), Err(BadArgType));
+        assert_eq!(parse_with(":kodis 3", &customs), Err(UnknownCommand));
+        assert_eq!(
+            parse_with(":w", &customs),
+            Err(IncompleteCommand(vec![":wiki"]))
+        );
+        assert_eq!(parse(":kodi 3"), Err(UnknownCommand));
+        assert!(is_builtin(":play"));
+        assert!(!is_builtin(":kodi"));
+    }
+
+    #[test]
+    fn ids() {
+        assert_eq!(parse_ids("1"), Ok(vec![1]));
+        assert_eq!(parse_ids("1 3 5-8"), Ok(vec![1, 3, 5, 6, 7, 8]));
+        assert_eq!(parse_ids(" 2  4 "), Ok(vec![2, 4]));
+        assert_eq!(parse_ids("4-2"), Err(BadArgType));
+        assert_eq!(parse_ids("a"), Err(BadArgType));
+        assert_eq!(parse_ids("1-"), Err(BadArgType));
+        assert_eq!(parse_ids("1-99999999999"), Err(ArgTooHigh));
+        assert_eq!(
+            filter_high_ids(parse(":download 1 3-5"), 5),
+            Err(ArgTooHigh)
+        );
+        assert_eq!(filter_high_ids(parse(":download 0 1"), 5), Err(IdZero));
+        assert_eq!(
+            filter_high_ids(parse(":download 1 3-4"), 5),
+            Ok(Download(vec![1, 3, 4]))
+        );
     }
 
     #[test]
@@ -342,7 +927,12 @@ mod tests {
         assert_eq!(parse_first(":next"), Err(UnknownCommand));
         assert_eq!(parse_first(":previous"), Err(UnknownCommand));
         assert_eq!(parse_first(":n"), Err(UnknownCommand));
-        assert_eq!(parse_first(":p"), Err(UnknownCommand));
+        assert_eq!(
+            parse_first(":p"),
+            Err(IncompleteCommand(vec![":playlist", ":private"]))
+        );
+        assert_eq!(parse_first(":downloads"), Ok(Downloads));
+        assert_eq!(parse_first(":download 1"), Err(UnknownCommand));
         assert_eq!(parse_first("12"), Ok(Query(String::from("12"))));
         assert_eq!(parse_first("110"), Ok(Query(String::from("110"))));
     }
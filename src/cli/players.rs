@@ -0,0 +1,165 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Number of lines of stderr kept for each player
+const STDERR_LINES: usize = 5;
+
+/// How long to wait for the end of stderr once a player exited, in case another process keeps it open
+const STDERR_DELAY: Duration = Duration::from_secs(1);
+
+struct Player {
+    title: String,
+    child: Child,
+    started: Instant,
+    stderr: Arc<Mutex<VecDeque<String>>>,
+    reader: Option<JoinHandle<()>>,
+    /// When the exit was first noticed
+    exited: Option<Instant>,
+}
+
+/// Players running in the background while the user continues browsing
+pub struct Players {
+    running: Vec<Player>,
+}
+
+impl Players {
+    pub fn new() -> Players {
+        Players {
+            running: Vec::new(),
+        }
+    }
+
+    /// Launch the command in the background, collecting the end of its stderr
+    pub fn spawn(&mut self, title: String, command: &mut Command) -> io::Result<()> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stderr = Arc::new(Mutex::new(VecDeque::new()));
+        let reader = child.stderr.take().map(|pipe| {
+            let lines = stderr.clone();
+            spawn(move || {
+                for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                    let mut lines = lines.lock().unwrap();
+                    if lines.len() == STDERR_LINES {
+                        lines.pop_front();
+                    }
+                    lines.push_back(line);
+                }
+            })
+        });
+
+        self.running.push(Player {
+            title,
+            child,
+            started: Instant::now(),
+            stderr,
+            reader,
+            exited: None,
+        });
+        Ok(())
+    }
+
+    /// Remove the players that exited, returning a message for each of them
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        self.running.retain_mut(|p| match p.child.try_wait() {
+            Ok(None) => true,
+            Ok(Some(status)) => {
+                let exited = *p.exited.get_or_insert_with(Instant::now);
+                let read = p.reader.as_ref().map_or(true, JoinHandle::is_finished);
+                if !read && exited.elapsed() < STDERR_DELAY {
+                    return true;
+                }
+                let mut stderr = p.stderr.lock().unwrap();
+                messages.push(exit_message(&p.title, status, stderr.make_contiguous()));
+                false
+            }
+            Err(err) => {
+                messages.push(format!("Lost track of \"{}\": {err}", p.title));
+                false
+            }
+        });
+        messages
+    }
+
+    /// Titles of the running players and for how long they have been running
+    pub fn list(&self) -> Vec<(String, Duration)> {
+        self.running
+            .iter()
+            .map(|p| (p.title.clone(), p.started.elapsed()))
+            .collect()
+    }
+
+    pub fn kill(&mut self, id: usize) -> io::Result<bool> {
+        match self.running.get_mut(id) {
+            Some(p) => {
+                p.child.kill()?;
+                p.child.wait()?;
+                self.running.remove(id);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+fn exit_message(title: &str, status: ExitStatus, stderr: &[String]) -> String {
+    if status.success() {
+        format!("\"{title}\" has finished playing")
+    } else if stderr.is_empty() {
+        format!("\"{title}\" exited with {status}")
+    } else {
+        format!("\"{title}\" exited with {status}:\n{}", stderr.join("\n"))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::thread::sleep;
+
+    #[test]
+    fn exit_messages() {
+        let mut players = Players::new();
+        players
+            .spawn(
+                "failing".into(),
+                Command::new("sh").args(["-c", "echo 'no such file' >&2; exit 2"]),
+            )
+            .unwrap();
+        players
+            .spawn("sleeping".into(), Command::new("sleep").arg("10"))
+            .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut messages = players.poll();
+        while messages.is_empty() && Instant::now() < deadline {
+            sleep(Duration::from_millis(10));
+            messages = players.poll();
+        }
+        assert_eq!(
+            messages,
+            vec!["\"failing\" exited with exit status: 2:\nno such file".to_string()]
+        );
+        assert_eq!(players.list().len(), 1);
+        assert!(players.kill(0).unwrap());
+        assert!(!players.kill(0).unwrap());
+        assert!(players.list().is_empty());
+    }
+}
@@ -10,22 +10,38 @@ use peertube_api::{channels::Channel, error, Comment, Instance, Video};
 use peertube_viewer_utils::host_from_handle;
 use preloadable_list::AsyncLoader;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicUsize, Ordering},
     Arc,
 };
 use std::thread::spawn;
 
+/// List of videos stored locally
+#[derive(Clone, PartialEq, Eq)]
+pub enum SavedList {
+    /// Videos of the history matching the query, from the latest view
+    History(String),
+    Bookmarks,
+    Playlist(String),
+}
+
 #[derive(Clone)]
 enum VideoMode {
     Search(String),
     Channel(String),
     Trending,
+    /// Host and uuid of the videos of the list, fetched again from their instance
+    Saved {
+        list: SavedList,
+        videos: Arc<[(String, String)]>,
+    },
 }
 
 pub struct Videos {
     instance: Arc<Instance>,
     mode: VideoMode,
     preload_res: AtomicBool,
+    /// Saved videos that couldn't be fetched, the offsets don't count them
+    skipped: AtomicUsize,
 }
 
 impl Videos {
@@ -33,6 +49,7 @@ impl Videos {
         Videos {
             instance,
             preload_res: AtomicBool::new(false),
+            skipped: AtomicUsize::new(0),
             mode: VideoMode::Search(query.to_owned()),
         }
     }
@@ -41,6 +58,7 @@ impl Videos {
         Videos {
             instance,
             preload_res: AtomicBool::new(false),
+            skipped: AtomicUsize::new(0),
             mode: VideoMode::Channel(handle.to_owned()),
         }
     }
@@ -49,19 +67,49 @@ impl Videos {
         Videos {
             instance,
             preload_res: AtomicBool::new(false),
+            skipped: AtomicUsize::new(0),
             mode: VideoMode::Trending,
         }
     }
 
+    pub fn new_saved(
+        instance: Arc<Instance>,
+        list: SavedList,
+        videos: Vec<(String, String)>,
+    ) -> Videos {
+        Videos {
+            instance,
+            preload_res: AtomicBool::new(false),
+            skipped: AtomicUsize::new(0),
+            mode: VideoMode::Saved {
+                list,
+                videos: videos.into(),
+            },
+        }
+    }
+
     pub fn preload_res(&self, should: bool) {
         self.preload_res.store(should, Ordering::SeqCst);
     }
 
     pub fn name(&self) -> &'static str {
-        match self.mode {
+        match &self.mode {
             VideoMode::Search(_) => "Video search",
             VideoMode::Trending => "Trending video",
             VideoMode::Channel(_) => "Channel videos",
+            VideoMode::Saved { list, .. } => match list {
+                SavedList::History(_) => "Viewing history",
+                SavedList::Bookmarks => "Bookmarks",
+                SavedList::Playlist(_) => "Local playlist",
+            },
+        }
+    }
+
+    /// The list browsed, if the videos come from a local list
+    pub fn saved_list(&self) -> Option<&SavedList> {
+        match &self.mode {
+            VideoMode::Saved { list, .. } => Some(list),
+            _ => None,
         }
     }
 }
@@ -83,6 +131,29 @@ impl AsyncLoader for Videos {
                 offset,
             ),
             VideoMode::Trending => self.instance.trending_videos(step, offset),
+            VideoMode::Saved { videos, .. } => {
+                // The videos are fetched in parallel, those that can't be fetched anymore are skipped
+                // The pages are loaded one after another, so the skipped count is the one of the previous pages
+                let skipped = self.skipped.load(Ordering::SeqCst);
+                let handles: Vec<_> = videos
+                    .iter()
+                    .skip(offset + skipped)
+                    .take(step)
+                    .cloned()
+                    .map(|(host, uuid)| {
+                        let instance = self.instance.clone();
+                        spawn(move || instance.single_video(&host, &uuid))
+                    })
+                    .collect();
+                let requested = handles.len();
+                let fetched: Vec<_> = handles
+                    .into_iter()
+                    .filter_map(|h| h.join().ok()?.ok())
+                    .collect();
+                let skipped = skipped + requested - fetched.len();
+                self.skipped.store(skipped, Ordering::SeqCst);
+                Ok((fetched, videos.len() - skipped))
+            }
         }
     }
 
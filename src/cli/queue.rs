@@ -0,0 +1,81 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use std::env::temp_dir;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Entry of a playlist
+pub struct Entry<'a> {
+    pub title: &'a str,
+    pub duration: u64,
+    pub url: &'a str,
+}
+
+/// Whether the player is known to play M3U playlists
+pub fn supports_playlists(player: &str) -> bool {
+    matches!(
+        Path::new(player).file_stem().and_then(|s| s.to_str()),
+        Some("mpv" | "vlc" | "cvlc" | "celluloid")
+    )
+}
+
+/// Path of a new playlist given to the player
+///
+/// Each launch has its own file since a detached player still reads the previous one
+pub fn playlist_path() -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    temp_dir().join(format!("peertube-viewer-rs-{}-{count}.m3u", process::id()))
+}
+
+/// Extended M3U playlist, so that the player can display the titles
+pub fn m3u(entries: &[Entry]) -> String {
+    let mut res = String::from("#EXTM3U\n");
+    for e in entries {
+        let title = e.title.replace(['\n', '\r'], " ");
+        writeln!(res, "#EXTINF:{},{}\n{}", e.duration, title, e.url)
+            .expect("Formating failed unexpectedly");
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn playlist() {
+        let entries = [
+            Entry {
+                title: "First talk",
+                duration: 1800,
+                url: "https://framatube.org/videos/watch/1",
+            },
+            Entry {
+                title: "Second\ntalk",
+                duration: 62,
+                url: "https://framatube.org/static/2.mp4",
+            },
+        ];
+        assert_eq!(
+            m3u(&entries),
+            "#EXTM3U\n\
+            #EXTINF:1800,First talk\n\
+            https://framatube.org/videos/watch/1\n\
+            #EXTINF:62,Second talk\n\
+            https://framatube.org/static/2.mp4\n"
+        );
+        assert_ne!(playlist_path(), playlist_path());
+        assert!(supports_playlists("/usr/bin/mpv"));
+        assert!(!supports_playlists("transmission-remote"));
+    }
+}
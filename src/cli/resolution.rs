@@ -0,0 +1,140 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+/// How to pick a resolution when the preferred one isn't available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prefer {
+    /// Highest resolution that isn't above the preferred one
+    HighestBelow,
+    /// Resolution closest to the preferred one, the lowest one in case of a tie
+    Closest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolutionPolicy {
+    pub preferred: Option<u64>,
+    pub max: Option<u64>,
+    pub prefer: Prefer,
+}
+
+impl ResolutionPolicy {
+    /// Whether the policy differs from just taking the first resolution
+    pub fn is_set(&self) -> bool {
+        self.preferred.is_some() || self.max.is_some()
+    }
+
+    /// Policy for a resolution explicitly asked by the user, which ignores the maximum
+    pub fn with_preferred(self, preferred: u64) -> ResolutionPolicy {
+        ResolutionPolicy {
+            preferred: Some(preferred),
+            max: None,
+            ..self
+        }
+    }
+
+    /// Index of the resolution to use among the available ones
+    ///
+    /// The audio-only resolution (id 0) is never picked
+    pub fn pick(&self, available: &[u64]) -> Option<usize> {
+        let videos = || available.iter().enumerate().filter(|(_, r)| **r != 0);
+        let lowest = || videos().min_by_key(|(_, r)| **r).map(|(i, _)| i);
+        let target = match self.preferred.or(self.max) {
+            Some(t) => self.max.map_or(t, |m| t.min(m)),
+            None => return videos().next().map(|(i, _)| i),
+        };
+
+        match self.prefer {
+            Prefer::HighestBelow => videos()
+                .filter(|(_, r)| **r <= target)
+                .max_by_key(|(_, r)| **r)
+                .map(|(i, _)| i)
+                .or_else(lowest),
+            Prefer::Closest => videos()
+                .filter(|(_, r)| self.max.map_or(true, |m| **r <= m))
+                .min_by_key(|(_, r)| (r.abs_diff(target), **r))
+                .map(|(i, _)| i)
+                .or_else(lowest),
+        }
+    }
+}
+
+impl Default for ResolutionPolicy {
+    fn default() -> ResolutionPolicy {
+        ResolutionPolicy {
+            preferred: None,
+            max: None,
+            prefer: Prefer::HighestBelow,
+        }
+    }
+}
+
+/// Parse a resolution given by the user (ex: `480p` or `480`)
+pub fn parse_resolution(input: &str) -> Option<u64> {
+    input.strip_suffix(['p', 'P']).unwrap_or(input).parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const AVAILABLE: [u64; 5] = [1080, 720, 480, 240, 0];
+
+    #[test]
+    fn highest_below() {
+        let policy = ResolutionPolicy {
+            preferred: Some(720),
+            max: None,
+            prefer: Prefer::HighestBelow,
+        };
+        assert_eq!(policy.pick(&AVAILABLE), Some(1));
+        assert_eq!(policy.with_preferred(600).pick(&AVAILABLE), Some(2));
+        assert_eq!(policy.with_preferred(144).pick(&AVAILABLE), Some(3));
+        assert_eq!(policy.with_preferred(2160).pick(&AVAILABLE), Some(0));
+        assert_eq!(policy.pick(&[0]), None);
+        assert_eq!(policy.pick(&[]), None);
+    }
+
+    #[test]
+    fn closest() {
+        let policy = ResolutionPolicy {
+            preferred: Some(600),
+            max: None,
+            prefer: Prefer::Closest,
+        };
+        assert_eq!(policy.pick(&AVAILABLE), Some(2));
+        assert_eq!(policy.with_preferred(900).pick(&AVAILABLE), Some(1));
+        assert_eq!(policy.with_preferred(100).pick(&AVAILABLE), Some(3));
+    }
+
+    #[test]
+    fn max() {
+        let policy = ResolutionPolicy {
+            preferred: Some(1080),
+            max: Some(480),
+            prefer: Prefer::Closest,
+        };
+        assert_eq!(policy.pick(&AVAILABLE), Some(2));
+        assert_eq!(policy.pick(&[1080, 720]), Some(1));
+
+        let policy = ResolutionPolicy {
+            preferred: None,
+            max: Some(720),
+            prefer: Prefer::HighestBelow,
+        };
+        assert_eq!(policy.pick(&AVAILABLE), Some(1));
+        assert_eq!(ResolutionPolicy::default().pick(&AVAILABLE), Some(0));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_resolution("480p"), Some(480));
+        assert_eq!(parse_resolution("720"), Some(720));
+        assert_eq!(parse_resolution("hd"), None);
+    }
+}
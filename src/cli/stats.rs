@@ -0,0 +1,188 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use super::history::Entry;
+
+use std::collections::{BTreeMap, HashMap};
+use time::{OffsetDateTime, UtcOffset};
+
+/// Number of weeks, months, channels and instances listed
+const WEEKS: usize = 8;
+const MONTHS: usize = 6;
+const TOP: usize = 5;
+
+/// Videos watched during a period, or from a channel or an instance
+#[derive(Debug, PartialEq, Eq)]
+pub struct Row {
+    pub label: String,
+    pub videos: usize,
+    /// Sum of the durations of the videos
+    pub seconds: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    /// Latest weeks first
+    pub weeks: Vec<Row>,
+    /// Latest months first
+    pub months: Vec<Row>,
+    pub channels: Vec<Row>,
+    pub instances: Vec<Row>,
+    pub videos: usize,
+    pub average_duration: Option<u64>,
+    /// Share of the videos started but stopped before being marked as seen
+    pub abandoned: Option<f64>,
+}
+
+/// Compute the statistics of the history, `abandoned` is the number of videos partly watched
+pub fn compute(entries: &[&Entry], abandoned: usize, offset: UtcOffset) -> Stats {
+    let mut weeks = BTreeMap::new();
+    let mut months = BTreeMap::new();
+    let mut channels = HashMap::new();
+    let mut instances = HashMap::new();
+
+    for e in entries {
+        let add = |row: &mut (usize, u64)| {
+            row.0 += 1;
+            row.1 += e.duration;
+        };
+
+        // Entries migrated from the old history format have no date
+        if e.viewed_at > 0 {
+            if let Ok(date) = OffsetDateTime::from_unix_timestamp(e.viewed_at) {
+                let date = date.to_offset(offset);
+                let (year, week, _) = date.to_iso_week_date();
+                add(weeks.entry((year, week)).or_default());
+                add(months.entry((date.year(), date.month() as u8)).or_default());
+            }
+        }
+        if !e.handle.is_empty() {
+            add(channels.entry(e.handle.as_str()).or_default());
+        }
+        if !e.host.is_empty() {
+            add(instances.entry(e.host.as_str()).or_default());
+        }
+    }
+
+    let known_durations: Vec<_> = entries
+        .iter()
+        .map(|e| e.duration)
+        .filter(|d| *d > 0)
+        .collect();
+    let started = entries.len() + abandoned;
+
+    Stats {
+        weeks: latest(weeks, WEEKS, |(year, week)| format!("{year}-W{week:02}")),
+        months: latest(months, MONTHS, |(year, month)| format!("{year}-{month:02}")),
+        channels: top(channels),
+        instances: top(instances),
+        videos: entries.len(),
+        average_duration: (!known_durations.is_empty())
+            .then(|| known_durations.iter().sum::<u64>() / known_durations.len() as u64),
+        abandoned: (started > 0).then(|| abandoned as f64 / started as f64),
+    }
+}
+
+fn latest<K: Ord + Copy>(
+    periods: BTreeMap<K, (usize, u64)>,
+    max: usize,
+    label: impl Fn(K) -> String,
+) -> Vec<Row> {
+    periods
+        .into_iter()
+        .rev()
+        .take(max)
+        .map(|(k, (videos, seconds))| Row {
+            label: label(k),
+            videos,
+            seconds,
+        })
+        .collect()
+}
+
+/// The most watched, by time then by number of videos
+fn top(counts: HashMap<&str, (usize, u64)>) -> Vec<Row> {
+    let mut rows: Vec<_> = counts
+        .into_iter()
+        .map(|(label, (videos, seconds))| Row {
+            label: label.to_owned(),
+            videos,
+            seconds,
+        })
+        .collect();
+    rows.sort_unstable_by(|a, b| {
+        (b.seconds, b.videos)
+            .cmp(&(a.seconds, a.videos))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    rows.truncate(TOP);
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(handle: &str, duration: u64, viewed_at: i64) -> Entry {
+        Entry {
+            uuid: format!("{handle}-{viewed_at}"),
+            host: handle.split_once('@').unwrap().1.into(),
+            title: String::new(),
+            handle: handle.into(),
+            duration,
+            viewed_at,
+        }
+    }
+
+    #[test]
+    fn history_stats() {
+        let entries = [
+            // Monday 2023-10-16
+            entry("framasoft@framatube.org", 3600, 1_697_450_000),
+            // Friday 2023-10-13
+            entry("blender@video.blender.org", 1800, 1_697_190_000),
+            entry("framasoft@framatube.org", 600, 1_697_180_000),
+            // Migrated from the old format
+            Entry {
+                uuid: "old".into(),
+                host: String::new(),
+                title: String::new(),
+                handle: String::new(),
+                duration: 0,
+                viewed_at: 0,
+            },
+        ];
+        let refs: Vec<_> = entries.iter().collect();
+        let stats = compute(&refs, 1, UtcOffset::UTC);
+
+        let row = |label: &str, videos, seconds| Row {
+            label: label.into(),
+            videos,
+            seconds,
+        };
+        assert_eq!(
+            stats,
+            Stats {
+                weeks: vec![row("2023-W42", 1, 3600), row("2023-W41", 2, 2400)],
+                months: vec![row("2023-10", 3, 6000)],
+                channels: vec![
+                    row("framasoft@framatube.org", 2, 4200),
+                    row("blender@video.blender.org", 1, 1800),
+                ],
+                instances: vec![
+                    row("framatube.org", 2, 4200),
+                    row("video.blender.org", 1, 1800),
+                ],
+                videos: 4,
+                average_duration: Some(2000),
+                abandoned: Some(0.2),
+            }
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use peertube_api::Video;
+
+/// Values available to the placeholders of the player arguments
+pub fn video_vars(video: &Video, start: u64) -> Vec<(&'static str, String)> {
+    vec![
+        ("title", video.name().to_owned()),
+        ("channel", video.channel_display().to_owned()),
+        ("uuid", video.uuid().to_owned()),
+        ("host", video.host().to_owned()),
+        ("watch_url", video.watch_url()),
+        ("duration", video.duration().to_string()),
+        ("start", start.to_string()),
+    ]
+}
+
+/// Values of the placeholders when the player is given a playlist, they are all empty since they
+/// would only describe its first video
+pub fn playlist_vars() -> Vec<(&'static str, String)> {
+    [
+        "title",
+        "channel",
+        "uuid",
+        "host",
+        "watch_url",
+        "duration",
+        "start",
+    ]
+    .into_iter()
+    .map(|name| (name, String::new()))
+    .collect()
+}
+
+/// Replace the `{name}` placeholders with their value
+///
+/// Unknown placeholders are kept as is and `{{`/`}}` are used to write literal braces
+pub fn expand(input: &str, vars: &[(&str, String)]) -> String {
+    let mut res = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(idx) = rest.find(['{', '}']) {
+        res.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            res.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        let value = rest[1..].find('}').and_then(|end| {
+            let name = &rest[1..end + 1];
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| (v, end + 2))
+        });
+        match value {
+            Some((v, len)) => {
+                res.push_str(v);
+                rest = &rest[len..];
+            }
+            None => {
+                res.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Quote the value so that it is passed as a single word to `sh`
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn placeholders() {
+        let vars = [("title", "A talk".to_string()), ("start", "42".to_string())];
+        assert_eq!(
+            expand("--force-media-title={title}", &vars),
+            "--force-media-title=A talk"
+        );
+        assert_eq!(expand("--start={start}", &vars), "--start=42");
+        assert_eq!(expand("{title} ({start})", &vars), "A talk (42)");
+        assert_eq!(expand("--volume=30", &vars), "--volume=30");
+        assert_eq!(expand("{unknown}", &vars), "{unknown}");
+        assert_eq!(expand("{{title}}", &vars), "{title}");
+        assert_eq!(expand("{title", &vars), "{title");
+        assert_eq!(expand("}{", &vars), "}{");
+        assert_eq!(
+            expand("--force-media-title={title}", &playlist_vars()),
+            "--force-media-title="
+        );
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(shell_quote("A talk"), "'A talk'");
+        assert_eq!(shell_quote("it's $HOME"), "'it'\\''s $HOME'");
+    }
+}
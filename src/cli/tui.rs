@@ -0,0 +1,766 @@
+// This file is part of peertube-viewer-rs.
+//
+// peertube-viewer-rs is free software: you can redistribute it and/or modify it under the terms of the GNU Affero General Public License as published by the Free Software Foundation, either version 3 of the License, or any later version.
+//
+// peertube-viewer-rs is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License along with peertube-viewer-rs. If not, see <https://www.gnu.org/licenses/>.
+
+use super::config::InitialInfo;
+use super::preloadables::{Channels, Comments, Videos};
+use super::{error_message, Cli, Mode, SEARCH_TOTAL};
+use crate::error::Error;
+
+use peertube_api::error::Error as ApiError;
+use peertube_api::Video;
+use preloadable_list::{AsyncLoader, PreloadableList};
+
+use std::fmt::Write as _;
+use std::io::{self, stdin, stdout, Stdout, Write};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::spawn;
+use std::time::Duration;
+
+use signal_hook::{consts::SIGWINCH, iterator::Signals};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{AlternateScreen, IntoAlternateScreen};
+use termion::{clear, cursor, style, terminal_size};
+
+const TABS: [&str; 3] = ["Videos", "Channels", "Comments"];
+const VIDEOS: usize = 0;
+const CHANNELS: usize = 1;
+const COMMENTS: usize = 2;
+
+/// How often the screen is redrawn while the details are loading
+const LOADING_TICK: Duration = Duration::from_millis(100);
+
+const HELP: &str = "j/k: move  n/p: page  enter: play  i: details  c: comments  o: browser  /: search  t: trending  tab: switch  q: quit";
+
+/// Lines longer than the terminal are cut instead of wrapped
+const NO_WRAP: &str = "\x1b[?7l";
+const WRAP: &str = "\x1b[?7h";
+
+enum Event {
+    Key(Key),
+    Resize,
+    /// Nothing happened before the timeout
+    Tick,
+    /// The input was closed
+    Closed,
+}
+
+/// Alternate screen in raw mode, that can be given back to run a player
+struct Screen {
+    terminal: Option<AlternateScreen<RawTerminal<Stdout>>>,
+    events: Receiver<Event>,
+    /// Asks the input thread for one key, so that it doesn't read stdin while the screen is suspended
+    read_key: Sender<()>,
+    pending: bool,
+}
+
+impl Screen {
+    fn new() -> io::Result<Screen> {
+        let (tx, events) = channel();
+        let (read_key, requests) = channel::<()>();
+        let keys_tx = tx.clone();
+        spawn(move || {
+            let mut keys = stdin().keys();
+            for () in requests {
+                let event = match keys.next() {
+                    Some(Ok(key)) => Event::Key(key),
+                    _ => Event::Closed,
+                };
+                if keys_tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        if let Ok(mut signals) = Signals::new([SIGWINCH]) {
+            spawn(move || {
+                for _ in signals.forever() {
+                    if tx.send(Event::Resize).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let mut screen = Screen {
+            terminal: None,
+            events,
+            read_key,
+            pending: false,
+        };
+        screen.resume()?;
+        Ok(screen)
+    }
+
+    fn resume(&mut self) -> io::Result<()> {
+        let mut terminal = stdout().into_raw_mode()?.into_alternate_screen()?;
+        write!(terminal, "{}{}", cursor::Hide, NO_WRAP)?;
+        terminal.flush()?;
+        self.terminal = Some(terminal);
+        Ok(())
+    }
+
+    fn suspend(&mut self) {
+        if let Some(mut terminal) = self.terminal.take() {
+            write!(terminal, "{}{}", cursor::Show, WRAP).unwrap_or(());
+            terminal.flush().unwrap_or(());
+        }
+    }
+
+    /// Waits at most `timeout` if it is given
+    fn next_event(&mut self, timeout: Option<Duration>) -> Event {
+        if !self.pending {
+            if self.read_key.send(()).is_err() {
+                return Event::Closed;
+            }
+            self.pending = true;
+        }
+        let event = match timeout {
+            Some(timeout) => match self.events.recv_timeout(timeout) {
+                Err(RecvTimeoutError::Timeout) => return Event::Tick,
+                event => event.ok(),
+            },
+            None => self.events.recv().ok(),
+        };
+        match event {
+            Some(Event::Resize) => Event::Resize,
+            Some(Event::Tick) => Event::Tick,
+            Some(Event::Key(key)) => {
+                self.pending = false;
+                Event::Key(key)
+            }
+            Some(Event::Closed) | None => Event::Closed,
+        }
+    }
+
+    fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+        if let Some(terminal) = &mut self.terminal {
+            for (n, line) in lines.iter().enumerate() {
+                write!(
+                    terminal,
+                    "{}{}{}{}",
+                    cursor::Goto(1, n as u16 + 1),
+                    line,
+                    style::Reset,
+                    clear::UntilNewline
+                )?;
+            }
+            terminal.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        self.suspend();
+    }
+}
+
+/// Position in the list of a tab
+#[derive(Default, Clone, Copy)]
+struct Cursor {
+    selected: usize,
+    /// First item shown
+    top: usize,
+}
+
+struct State {
+    modes: [Mode; 3],
+    cursors: [Cursor; 3],
+    tab: usize,
+    details: bool,
+    /// Shown instead of the help until the next key
+    status: Option<String>,
+    /// Query being typed after `/`
+    search: Option<String>,
+    /// Index in the page of the items shown, blocked items are hidden
+    ids: Vec<usize>,
+    /// Video shown in the details whose description is being fetched
+    loading: Option<Arc<Video>>,
+}
+
+impl State {
+    fn new() -> State {
+        State {
+            modes: [Mode::Temp, Mode::Temp, Mode::Temp],
+            cursors: [Cursor::default(); 3],
+            tab: VIDEOS,
+            details: true,
+            status: None,
+            search: None,
+            ids: Vec::new(),
+            loading: None,
+        }
+    }
+
+    /// Index in the page of the selected item
+    fn selected(&self) -> Option<usize> {
+        self.ids.get(self.cursors[self.tab].selected).copied()
+    }
+}
+
+/// Returns false if there is no next page
+fn next_page<L>(list: &mut PreloadableList<L>) -> Result<bool, Error>
+where
+    L: AsyncLoader<Error = ApiError>,
+{
+    match list.try_next() {
+        Ok([]) => {
+            list.prev();
+            Ok(false)
+        }
+        Ok(_) => Ok(true),
+        Err(err) => {
+            list.prev();
+            Err(err.into())
+        }
+    }
+}
+
+fn prev_page<L: AsyncLoader>(list: &mut PreloadableList<L>) -> bool {
+    if list.offset() == 0 {
+        false
+    } else {
+        list.prev();
+        true
+    }
+}
+
+fn summary<L: AsyncLoader>(name: &str, list: &PreloadableList<L>) -> String {
+    format!(
+        "{} results {} to {} out of {}",
+        name,
+        list.offset(),
+        list.offset() + list.current_len(),
+        list.expected_total(),
+    )
+}
+
+impl Mode {
+    fn next_page(&mut self) -> Result<bool, Error> {
+        match self {
+            Mode::Videos(v) => next_page(v),
+            Mode::Channels(c) => next_page(c),
+            Mode::Comments(c) => next_page(c),
+            Mode::Downloads | Mode::Temp => Ok(false),
+        }
+    }
+
+    fn prev_page(&mut self) -> bool {
+        match self {
+            Mode::Videos(v) => prev_page(v),
+            Mode::Channels(c) => prev_page(c),
+            Mode::Comments(c) => prev_page(c),
+            Mode::Downloads | Mode::Temp => false,
+        }
+    }
+
+    /// Load the details of the selected item, and the next page when it gets close
+    fn preload(&mut self, id: usize, near_end: bool) {
+        match self {
+            Mode::Videos(v) => {
+                v.preload_id(id);
+                if near_end {
+                    v.preload_next();
+                }
+            }
+            Mode::Channels(c) => {
+                c.preload_id(id);
+                if near_end {
+                    c.preload_next();
+                }
+            }
+            Mode::Comments(c) => {
+                c.preload_id(id);
+                if near_end {
+                    c.preload_next();
+                }
+            }
+            Mode::Downloads | Mode::Temp => {}
+        }
+    }
+}
+
+impl Cli {
+    /// Main loop for the full-screen interface
+    pub(super) fn tui(&mut self) -> Result<(), Error> {
+        let initial = match self.initial_info.take() {
+            InitialInfo::VideoUrl(s) => {
+                self.play_vid(&self.instance.single_video(self.instance.host(), &s)?, None)?;
+                return Ok(());
+            }
+            InitialInfo::Stats => {
+                self.show_stats();
+                return Ok(());
+            }
+            InitialInfo::Query(s) => Some((
+                VIDEOS,
+                Mode::Videos(PreloadableList::new(
+                    Videos::new_search(self.instance.clone(), &s),
+                    SEARCH_TOTAL,
+                )),
+            )),
+            InitialInfo::Channels(s) => Some((
+                CHANNELS,
+                Mode::Channels(PreloadableList::new(
+                    Channels::new(self.instance.clone(), &s),
+                    SEARCH_TOTAL,
+                )),
+            )),
+            InitialInfo::Handle(s) => Some((
+                VIDEOS,
+                Mode::Videos(PreloadableList::new(
+                    Videos::new_channel(self.instance.clone(), &s),
+                    SEARCH_TOTAL,
+                )),
+            )),
+            InitialInfo::Trending | InitialInfo::None if !self.config.is_search_engine() => Some((
+                VIDEOS,
+                Mode::Videos(PreloadableList::new(
+                    Videos::new_trending(self.instance.clone()),
+                    SEARCH_TOTAL,
+                )),
+            )),
+            InitialInfo::Trending | InitialInfo::None => None,
+        };
+
+        let mut screen = Screen::new().map_err(Error::Terminal)?;
+        let mut state = State::new();
+        if let Some((tab, mode)) = initial {
+            self.tui_loading(&mut screen, &mut state)?;
+            let result = self.tui_open(&mut state, tab, mode);
+            self.tui_report(&mut state, result)?;
+        }
+
+        loop {
+            let frame = self.tui_frame(&mut state);
+            screen.draw(&frame).map_err(Error::Terminal)?;
+            let timeout = state.loading.as_ref().map(|_| LOADING_TICK);
+            let key = match screen.next_event(timeout) {
+                Event::Key(key) => key,
+                Event::Resize | Event::Tick => continue,
+                Event::Closed => return Ok(()),
+            };
+
+            if state.search.is_some() {
+                let result = self.tui_search_key(&mut screen, &mut state, key);
+                self.tui_report(&mut state, result)?;
+                continue;
+            }
+
+            state.status = None;
+            let result = match key {
+                Key::Char('q') | Key::Ctrl('c') | Key::Ctrl('d') => return Ok(()),
+                Key::Char('j') | Key::Down => self.tui_move(&mut screen, &mut state, true),
+                Key::Char('k') | Key::Up => self.tui_move(&mut screen, &mut state, false),
+                Key::Char('n') | Key::PageDown => {
+                    self.tui_page(&mut screen, &mut state, true).map(|_| ())
+                }
+                Key::Char('p') | Key::PageUp => {
+                    self.tui_page(&mut screen, &mut state, false).map(|_| ())
+                }
+                Key::Char('\t') | Key::Right => {
+                    state.tab = (state.tab + 1) % TABS.len();
+                    Ok(())
+                }
+                Key::BackTab | Key::Left => {
+                    state.tab = (state.tab + TABS.len() - 1) % TABS.len();
+                    Ok(())
+                }
+                Key::Char(c @ '1'..='3') => {
+                    state.tab = c as usize - '1' as usize;
+                    Ok(())
+                }
+                Key::Char('i') => {
+                    state.details = !state.details;
+                    Ok(())
+                }
+                Key::Char('\n') => self.tui_enter(&mut screen, &mut state),
+                Key::Char('c') => self.tui_comments(&mut screen, &mut state),
+                Key::Char('o') => self.tui_browser(&mut screen, &mut state),
+                Key::Char('t') => self.tui_trending(&mut screen, &mut state),
+                Key::Char('/') => {
+                    state.search = Some(String::new());
+                    Ok(())
+                }
+                _ => Ok(()),
+            };
+            self.tui_report(&mut state, result)?;
+
+            for msg in self.players.poll() {
+                state.status = Some(self.display.theme().warning.paint(msg).to_string());
+            }
+        }
+    }
+
+    /// Lines of the whole screen
+    fn tui_frame(&self, state: &mut State) -> Vec<String> {
+        let (cols, rows) = terminal_size()
+            .map(|(c, r)| (c as usize, r as usize))
+            .unwrap_or((80, 24));
+        let theme = self.display.theme();
+        let mut lines = Vec::with_capacity(rows);
+
+        let mut tabs = String::new();
+        for (i, name) in TABS.iter().enumerate() {
+            if i == state.tab {
+                write!(
+                    tabs,
+                    "{} {} {} {}",
+                    style::Invert,
+                    i + 1,
+                    name,
+                    style::Reset
+                )
+            } else {
+                write!(tabs, " {} {} ", i + 1, name)
+            }
+            .expect("Formatting failed unexpectedly");
+        }
+        lines.push(tabs);
+
+        let list_cols = cols.saturating_sub(2);
+        let (info, items) = match &state.modes[state.tab] {
+            Mode::Videos(v) => (
+                summary(v.loader().name(), v),
+                self.display
+                    .video_rows(v.current(), &self.history, &self.config, list_cols),
+            ),
+            Mode::Channels(c) => (
+                summary("Channel search", c),
+                self.display
+                    .channel_rows(c.current(), &self.config, list_cols),
+            ),
+            Mode::Comments(c) => (
+                summary("Browsing video comments", c),
+                self.display
+                    .comment_rows(c.current(), &self.config, list_cols),
+            ),
+            Mode::Downloads | Mode::Temp if state.tab == COMMENTS => (
+                "Press c on a video to browse its comments".to_string(),
+                Vec::new(),
+            ),
+            Mode::Downloads | Mode::Temp => ("Press / to search".to_string(), Vec::new()),
+        };
+        lines.push(theme.info.paint(info).to_string());
+        state.ids = items.iter().map(|(id, _)| *id).collect();
+
+        let body = rows.saturating_sub(3);
+        let details_height = if state.details && body >= 10 {
+            body / 2
+        } else {
+            0
+        };
+        let list_height = body - details_height;
+
+        let cursor = &mut state.cursors[state.tab];
+        cursor.selected = cursor.selected.min(items.len().saturating_sub(1));
+        cursor.top = cursor.top.min(cursor.selected);
+        let heights: Vec<usize> = items.iter().map(|(_, r)| r.split('\n').count()).collect();
+        while cursor.top < cursor.selected
+            && heights[cursor.top..=cursor.selected].iter().sum::<usize>() > list_height
+        {
+            cursor.top += 1;
+        }
+
+        let mut list = Vec::with_capacity(list_height);
+        'rows: for (pos, (_, row)) in items.iter().enumerate().skip(cursor.top) {
+            for (n, line) in row.split('\n').enumerate() {
+                if list.len() >= list_height {
+                    break 'rows;
+                }
+                if pos == cursor.selected && n == 0 {
+                    list.push(format!("{} {}", theme.prompt.paint(">"), line));
+                } else {
+                    list.push(format!("  {line}"));
+                }
+            }
+        }
+        list.resize(list_height, String::new());
+        lines.append(&mut list);
+
+        let mut loading = None;
+        if details_height > 0 {
+            let details = match (&state.modes[state.tab], items.get(cursor.selected)) {
+                (Mode::Videos(v), Some((id, _))) => {
+                    let video = &v.current()[*id];
+                    if video.loaded_description().is_none() {
+                        // The first item of a page isn't preloaded when it is selected
+                        if !matches!(&state.loading, Some(l) if Arc::ptr_eq(l, video)) {
+                            v.preload_id(*id);
+                        }
+                        loading = Some(video.clone());
+                    }
+                    self.display.render_loaded_video_info(video, cols)
+                }
+                (Mode::Channels(c), Some((id, _))) => {
+                    self.display.render_channel_info(&c.current()[*id], cols)
+                }
+                (Mode::Comments(c), Some((id, _))) => {
+                    self.display.render_comment_info(&c.current()[*id], cols)
+                }
+                _ => String::new(),
+            };
+            let mut details: Vec<String> = details
+                .lines()
+                .take(details_height)
+                .map(str::to_owned)
+                .collect();
+            details.resize(details_height, String::new());
+            lines.append(&mut details);
+        }
+
+        state.loading = loading;
+
+        lines.push(match (&state.search, &state.status) {
+            (Some(query), _) => format!(
+                "{} {}{} {}",
+                theme.prompt.paint("/"),
+                query,
+                style::Invert,
+                style::Reset
+            ),
+            (None, Some(status)) => status.clone(),
+            (None, None) => theme.hint.paint(HELP).to_string(),
+        });
+        lines.truncate(rows);
+        lines
+    }
+
+    /// Show that a request is being made before it blocks
+    fn tui_loading(&self, screen: &mut Screen, state: &mut State) -> Result<(), Error> {
+        let status = state
+            .status
+            .replace(self.display.theme().info.paint("Loading…").to_string());
+        let frame = self.tui_frame(state);
+        state.status = status;
+        screen.draw(&frame).map_err(Error::Terminal)
+    }
+
+    /// Show the error in the status line, only the errors of the terminal stop the interface
+    fn tui_report(&self, state: &mut State, result: Result<(), Error>) -> Result<(), Error> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(err @ Error::Terminal(_)) => Err(err),
+            Err(err) => {
+                let message = error_message(&err);
+                if let Some(message) = &message {
+                    state.status = Some(self.display.theme().error.paint(message).to_string());
+                }
+                self.error_hook(message);
+                Ok(())
+            }
+        }
+    }
+
+    /// Load the first page of a list and show it in its tab
+    fn tui_open(&self, state: &mut State, tab: usize, mut mode: Mode) -> Result<(), Error> {
+        mode.ensure_init()?;
+        if let Mode::Videos(videos) = &mode {
+            videos.loader().preload_res(
+                self.config.select_quality()
+                    || self.config.use_raw_url()
+                    || self.config.resolution_policy().is_set(),
+            );
+        }
+        state.modes[tab] = mode;
+        state.cursors[tab] = Cursor::default();
+        state.tab = tab;
+        Ok(())
+    }
+
+    fn tui_search_key(
+        &mut self,
+        screen: &mut Screen,
+        state: &mut State,
+        key: Key,
+    ) -> Result<(), Error> {
+        match key {
+            Key::Char('\n') => {
+                let query = state.search.take().unwrap_or_default();
+                let query = query.trim();
+                if query.is_empty() {
+                    return Ok(());
+                }
+                self.tui_loading(screen, state)?;
+                if state.tab == CHANNELS {
+                    self.rl.add_history_entry(&format!(":channels {query}"));
+                    let channels = Channels::new(self.instance.clone(), query);
+                    self.tui_open(
+                        state,
+                        CHANNELS,
+                        Mode::Channels(PreloadableList::new(channels, SEARCH_TOTAL)),
+                    )
+                } else {
+                    self.rl.add_history_entry(query);
+                    let videos = Videos::new_search(self.instance.clone(), query);
+                    self.tui_open(
+                        state,
+                        VIDEOS,
+                        Mode::Videos(PreloadableList::new(videos, SEARCH_TOTAL)),
+                    )
+                }
+            }
+            Key::Esc | Key::Ctrl('c') => {
+                state.search = None;
+                Ok(())
+            }
+            Key::Backspace => {
+                if let Some(query) = &mut state.search {
+                    query.pop();
+                }
+                Ok(())
+            }
+            Key::Char(c) => {
+                if let Some(query) = &mut state.search {
+                    query.push(c);
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn tui_move(
+        &mut self,
+        screen: &mut Screen,
+        state: &mut State,
+        down: bool,
+    ) -> Result<(), Error> {
+        let len = state.ids.len();
+        let cursor = &mut state.cursors[state.tab];
+        if down && cursor.selected + 1 < len {
+            cursor.selected += 1;
+        } else if !down && cursor.selected > 0 {
+            cursor.selected -= 1;
+        } else {
+            if self.tui_page(screen, state, down)? && !down {
+                state.cursors[state.tab].selected = usize::MAX;
+            }
+            return Ok(());
+        }
+
+        let selected = cursor.selected;
+        state.modes[state.tab].preload(state.ids[selected], selected + 5 >= len);
+        Ok(())
+    }
+
+    /// Returns false if there is no page in this direction
+    fn tui_page(
+        &mut self,
+        screen: &mut Screen,
+        state: &mut State,
+        next: bool,
+    ) -> Result<bool, Error> {
+        let turned = if next {
+            self.tui_loading(screen, state)?;
+            state.modes[state.tab].next_page()?
+        } else {
+            state.modes[state.tab].prev_page()
+        };
+        if turned {
+            state.cursors[state.tab] = Cursor::default();
+        }
+        Ok(turned)
+    }
+
+    fn tui_enter(&mut self, screen: &mut Screen, state: &mut State) -> Result<(), Error> {
+        let id = match state.selected() {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        match &state.modes[state.tab] {
+            Mode::Videos(v) => {
+                let video = v.current()[id].clone();
+                screen.suspend();
+                let result = self.play_vid(&video, None);
+                screen.resume().map_err(Error::Terminal)?;
+                result.map(|_| ())
+            }
+            Mode::Channels(c) => {
+                let handle = c.current()[id].handle();
+                self.tui_loading(screen, state)?;
+                self.rl.add_history_entry(&format!(":chandle {handle}"));
+                let videos = Videos::new_channel(self.instance.clone(), &handle);
+                self.tui_open(
+                    state,
+                    VIDEOS,
+                    Mode::Videos(PreloadableList::new(videos, SEARCH_TOTAL)),
+                )
+            }
+            Mode::Comments(_) => self.tui_browser(screen, state),
+            Mode::Downloads | Mode::Temp => Ok(()),
+        }
+    }
+
+    fn tui_comments(&mut self, screen: &mut Screen, state: &mut State) -> Result<(), Error> {
+        let comments = match (&state.modes[state.tab], state.selected()) {
+            (Mode::Videos(v), Some(id)) => {
+                let video = &v.current()[id];
+                Comments::new(
+                    self.instance.clone(),
+                    video.host().to_owned(),
+                    video.uuid().to_owned(),
+                )
+            }
+            (Mode::Videos(_), None) => return Ok(()),
+            _ => {
+                state.status = Some(
+                    self.display
+                        .theme()
+                        .warning
+                        .paint("Only videos have comments")
+                        .to_string(),
+                );
+                return Ok(());
+            }
+        };
+        self.tui_loading(screen, state)?;
+        self.tui_open(
+            state,
+            COMMENTS,
+            Mode::Comments(PreloadableList::new(comments, SEARCH_TOTAL)),
+        )
+    }
+
+    fn tui_browser(&mut self, screen: &mut Screen, state: &mut State) -> Result<(), Error> {
+        let id = match state.selected() {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        screen.suspend();
+        let result = self.open_browser(&state.modes[state.tab], id + 1);
+        screen.resume().map_err(Error::Terminal)?;
+        result
+    }
+
+    fn tui_trending(&mut self, screen: &mut Screen, state: &mut State) -> Result<(), Error> {
+        if self.config.is_search_engine() {
+            state.status = Some(
+                self.display
+                    .theme()
+                    .warning
+                    .paint(
+                        "Trending results are not available when using a search engine such as sepia.",
+                    )
+                    .to_string(),
+            );
+            return Ok(());
+        }
+        self.tui_loading(screen, state)?;
+        self.rl.add_history_entry(":trending");
+        let videos = Videos::new_trending(self.instance.clone());
+        self.tui_open(
+            state,
+            VIDEOS,
+            Mode::Videos(PreloadableList::new(videos, SEARCH_TOTAL)),
+        )
+    }
+}
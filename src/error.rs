@@ -17,7 +17,9 @@ pub enum Error {
     Readline(rustyline::error::ReadlineError),
     VideoLaunch(io::Error),
     BrowserLaunch(io::Error),
+    CommandLaunch(io::Error),
     Stdin(io::Error),
+    Terminal(io::Error),
     BlockedInstance(String),
 }
 
@@ -29,6 +31,8 @@ impl fmt::Display for Error {
             Error::Readline(_) | Error::Stdin(_) => write!(f, "Input error"),
             Error::VideoLaunch(_) => write!(f, "Unable to launch video"),
             Error::BrowserLaunch(_) => write!(f, "Unable to launch video"),
+            Error::CommandLaunch(_) => write!(f, "Unable to launch command"),
+            Error::Terminal(_) => write!(f, "Unable to use the terminal"),
             Error::BlockedInstance(s) => write!(f, "Can't connect to a blocked instance: {s}"),
         }
     }
@@ -43,6 +47,8 @@ impl error::Error for Error {
             Error::Stdin(err) => Some(err),
             Error::VideoLaunch(err) => Some(err),
             Error::BrowserLaunch(err) => Some(err),
+            Error::CommandLaunch(err) => Some(err),
+            Error::Terminal(err) => Some(err),
             Error::BlockedInstance(_) => None,
         }
     }